@@ -0,0 +1,840 @@
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::{
+    Arg, ArgAction, ArgMatches, Command, CommandFactory, FromArgMatches, Parser, ValueEnum,
+    parser::ValueSource,
+};
+use lookfor::expr::{Expr, Token};
+#[cfg(unix)]
+use lookfor::matcher::{Access, Owner, Perm};
+use lookfor::matcher::{
+    Bytes, Case, Contains, Empty, Extension, FsType, Kind, Mime, Name, Or, Size, Time, TimeField,
+    Timestamp,
+};
+use lookfor::{Error, FileTypeFilter, Query, SortKey};
+use regex::bytes::Regex;
+
+use crate::color::{ColorChoice, Colors, Highlight};
+use crate::output::{Field, Format, Printer};
+use crate::template::Template;
+
+const EXPRESSION_HELP: &str = "\
+Tests are ANDed by default. Repeating --name, --ext, --type, --fstype,
+--contains, --mime or --kind back to back matches any of the values
+instead; other repeated tests stay ANDed, so --size +1k --size -10M is a
+range. Combine tests find-style with -o/--or, -a/--and, !/--not and
+group with ( and ), e.g.
+
+    lookfor src ( --name foo -o --ext md ) ! --type dir
+
+Remember to quote or escape ! ( ) for your shell. Use --explain to see how
+an expression was parsed.";
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "lookfor",
+    version,
+    about = "A small, fast Rust-powered alternative to `find`.",
+    after_help = EXPRESSION_HELP
+)]
+pub struct Args {
+    /// Root path to start searching from
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Match on file/directory name (substring, regex or glob); repeat to
+    /// match any of several
+    #[arg(short, long)]
+    pub name: Vec<String>,
+
+    /// Treat --name and --contains as regular expressions
+    #[arg(long, conflicts_with = "glob")]
+    pub regex: bool,
+
+    /// Treat --name as a glob (e.g. '*.test.[jt]s', 'src/**/*.{rs,toml}')
+    #[arg(long)]
+    pub glob: bool,
+
+    /// Match --name against the path relative to the search root
+    #[arg(long)]
+    pub full_path: bool,
+
+    /// Ignore case in --name, --ext and --contains (default: only when the
+    /// pattern is all lowercase). Plain --name patterns use full Unicode
+    /// folding ('strasse' matches 'Straße'); --regex, --glob and --contains
+    /// fold one character at a time, so they don't
+    #[arg(short, long, overrides_with = "case_sensitive")]
+    pub ignore_case: bool,
+
+    /// Match case exactly in --name, --ext and --contains
+    #[arg(short = 's', long, overrides_with = "ignore_case")]
+    pub case_sensitive: bool,
+
+    /// Match on file extension (e.g. 'rs', 'txt'); repeat or give a comma
+    /// list ('rs,toml,md') to match any of several
+    #[arg(short, long, value_delimiter = ',')]
+    pub ext: Vec<String>,
+
+    /// Match files by size: '+100M' (more than), '-4k' (less than), '1M..2G'
+    /// (inclusive range) or an exact size; units b, k, M, G, T (x1024) or
+    /// KB, MB, GB, TB (x1000)
+    #[arg(long, value_name = "SIZE", allow_hyphen_values = true)]
+    pub size: Vec<Size>,
+
+    /// Match empty files and directories without entries
+    #[arg(long)]
+    pub empty: bool,
+
+    /// Match files with a line containing PATTERN (a regex with --regex);
+    /// checked after the other tests, repeat or give several to match any
+    #[arg(long, value_name = "PATTERN", help_heading = "Content search")]
+    pub contains: Vec<String>,
+
+    /// Don't search the contents of files larger than SIZE (e.g. '10M')
+    #[arg(long, value_name = "SIZE", help_heading = "Content search")]
+    pub max_filesize: Option<Bytes>,
+
+    /// Print the matching lines, with line numbers, below each file (plain
+    /// output only)
+    #[arg(long, requires = "contains", help_heading = "Content search")]
+    pub show_matches: bool,
+
+    /// Match files whose contents look like this MIME type or glob (e.g.
+    /// 'application/pdf', 'image/*'); repeat to match any of several
+    #[arg(long, value_name = "TYPE", help_heading = "Content search")]
+    pub mime: Vec<String>,
+
+    /// Match files whose contents are of this kind: image, archive, text, elf
+    /// or script (by #! line); repeat or give a comma list to match any
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help_heading = "Content search"
+    )]
+    pub kind: Vec<Kind>,
+
+    /// Match entries modified at or after TIME: a duration back from now
+    /// ('90s', '2h', '3d', '1w', '1h30m') or a date ('2024-05-01',
+    /// '2024-05-01T13:30', with optional seconds and offset)
+    #[arg(long, value_name = "TIME", help_heading = "Time tests")]
+    pub changed_within: Vec<Timestamp>,
+
+    /// Match entries modified before TIME
+    #[arg(long, value_name = "TIME", help_heading = "Time tests")]
+    pub changed_before: Vec<Timestamp>,
+
+    /// Match entries modified more recently than FILE was
+    #[arg(long, value_name = "FILE", help_heading = "Time tests")]
+    pub newer: Vec<PathBuf>,
+
+    /// Match entries accessed at or after TIME
+    #[arg(long, value_name = "TIME", help_heading = "Time tests")]
+    pub accessed_within: Vec<Timestamp>,
+
+    /// Match entries accessed before TIME
+    #[arg(long, value_name = "TIME", help_heading = "Time tests")]
+    pub accessed_before: Vec<Timestamp>,
+
+    /// Match entries accessed more recently than FILE was modified
+    #[arg(long, value_name = "FILE", help_heading = "Time tests")]
+    pub anewer: Vec<PathBuf>,
+
+    /// Match entries whose status (mode, owner, ...) changed at or after TIME
+    #[arg(long, value_name = "TIME", help_heading = "Time tests")]
+    pub ctime_within: Vec<Timestamp>,
+
+    /// Match entries whose status changed before TIME
+    #[arg(long, value_name = "TIME", help_heading = "Time tests")]
+    pub ctime_before: Vec<Timestamp>,
+
+    /// Match entries whose status changed more recently than FILE was modified
+    #[arg(long, value_name = "FILE", help_heading = "Time tests")]
+    pub cnewer: Vec<PathBuf>,
+
+    /// Match entries created at or after TIME (where the filesystem records it)
+    #[arg(long, value_name = "TIME", help_heading = "Time tests")]
+    pub created_within: Vec<Timestamp>,
+
+    /// Match entries created before TIME
+    #[arg(long, value_name = "TIME", help_heading = "Time tests")]
+    pub created_before: Vec<Timestamp>,
+
+    /// Match entries created more recently than FILE was modified
+    #[arg(long, value_name = "FILE", help_heading = "Time tests")]
+    pub bnewer: Vec<PathBuf>,
+
+    /// Match entries on a filesystem of this type (e.g. 'ext4', 'tmpfs');
+    /// repeat or give a comma list to match any of several
+    #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+    pub fstype: Vec<String>,
+
+    /// Match permission bits exactly: octal ('644') or symbolic ('u=rw,go=r');
+    /// prefix with '-' to require all of the bits or '/' for any of them
+    #[cfg(unix)]
+    #[arg(
+        long,
+        value_name = "MODE",
+        allow_hyphen_values = true,
+        help_heading = "Ownership and permission tests"
+    )]
+    pub perm: Vec<Perm>,
+
+    /// Match entries owned by USER (name or numeric id)
+    #[cfg(unix)]
+    #[arg(
+        long,
+        value_name = "USER",
+        help_heading = "Ownership and permission tests"
+    )]
+    pub user: Vec<String>,
+
+    /// Match entries owned by GROUP (name or numeric id)
+    #[cfg(unix)]
+    #[arg(
+        long,
+        value_name = "GROUP",
+        help_heading = "Ownership and permission tests"
+    )]
+    pub group: Vec<String>,
+
+    /// Match entries whose owner id has no user
+    #[cfg(unix)]
+    #[arg(long, help_heading = "Ownership and permission tests")]
+    pub nouser: bool,
+
+    /// Match entries whose group id has no group
+    #[cfg(unix)]
+    #[arg(long, help_heading = "Ownership and permission tests")]
+    pub nogroup: bool,
+
+    /// Match entries the current user may read
+    #[cfg(unix)]
+    #[arg(long, help_heading = "Ownership and permission tests")]
+    pub readable: bool,
+
+    /// Match entries the current user may write
+    #[cfg(unix)]
+    #[arg(long, help_heading = "Ownership and permission tests")]
+    pub writable: bool,
+
+    /// Match entries the current user may execute (or enter, for directories)
+    #[cfg(unix)]
+    #[arg(long, help_heading = "Ownership and permission tests")]
+    pub executable: bool,
+
+    /// Don't respect .gitignore, .ignore, .lookforignore or git excludes
+    #[arg(long)]
+    pub no_ignore: bool,
+
+    /// Don't respect .gitignore, .git/info/exclude or core.excludesFile
+    #[arg(long)]
+    pub no_ignore_vcs: bool,
+
+    /// Minimum depth of reported entries (0 = the root itself, 1 = skip it)
+    #[arg(long, value_name = "N")]
+    pub min_depth: Option<usize>,
+
+    /// Maximum depth to descend to (0 = only the root, 1 = its entries)
+    #[arg(long, value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// Only report entries at exactly depth N
+    #[arg(long, value_name = "N", conflicts_with_all = ["min_depth", "max_depth"])]
+    pub exact_depth: Option<usize>,
+
+    /// Follow symlinks: descend into linked directories and test links by
+    /// their targets; links leading back up are reported, not followed
+    #[arg(short = 'L', long)]
+    pub follow: bool,
+
+    /// Descend through at most N symlinks on any one path
+    #[arg(long, value_name = "N", requires = "follow")]
+    pub follow_depth: Option<usize>,
+
+    /// Don't descend into directories on other filesystems than PATH's
+    #[arg(short = 'x', long)]
+    pub one_file_system: bool,
+
+    /// Skip filesystems of these types, e.g. 'nfs,fuse,tmpfs' ('nfs' also
+    /// covers nfs4, 'fuse' covers fuse.sshfs); nothing is reported for a
+    /// root on one of them
+    #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+    pub exclude_fstype: Vec<String>,
+
+    /// Include hidden files and directories
+    #[arg(long)]
+    pub hidden: bool,
+
+    /// Don't descend into directories matching this glob (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    pub exclude_dir: Vec<String>,
+
+    /// Filter on type: file (f), dir (d), symlink (l), broken-symlink,
+    /// socket, fifo, block-device, char-device, executable (x) or any; repeat
+    /// or give a comma list to match any of several
+    #[arg(short, long, value_enum, value_delimiter = ',')]
+    pub r#type: Vec<FileTypeFilter>,
+
+    /// Number of walker threads (0 = one per CPU); output order is
+    /// unspecified with more than one unless --sort is given
+    #[arg(short = 'j', long, default_value_t = 1, value_name = "N")]
+    pub threads: usize,
+
+    /// Report matches sorted by this key instead of as they are found; size
+    /// and mtime put the largest and newest first
+    #[arg(long, value_enum, value_name = "KEY")]
+    pub sort: Option<SortKey>,
+
+    /// Reverse the --sort order
+    #[arg(long, requires = "sort")]
+    pub reverse: bool,
+
+    /// Only report the first N matches in --sort order, e.g. '--sort size
+    /// --top 10' for the 10 largest
+    #[arg(long, value_name = "N", requires = "sort")]
+    pub top: Option<usize>,
+
+    /// Output format: plain paths, or records as json, ndjson or csv
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t = Format::Plain)]
+    pub output: Format,
+
+    /// Print each match as TEMPLATE, e.g. '{size:h}\t{mtime:%Y-%m-%d}\t{path}';
+    /// placeholders are the --fields names plus stem and parent
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "output")]
+    pub format: Option<String>,
+
+    /// Color paths by type and extension (from LS_COLORS) and highlight
+    /// --name matches
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Long listing: permissions, links, owner, group, size and mtime in
+    /// aligned columns before each path
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = ["output", "format", "print0", "show_matches"]
+    )]
+    pub long: bool,
+
+    /// Draw matches under their ancestor directories, like `tree`; chains of
+    /// lone directories are collapsed and directories show how many matches
+    /// they contain
+    #[arg(
+        long,
+        conflicts_with_all = ["output", "format", "print0", "show_matches", "long"]
+    )]
+    pub tree: bool,
+
+    /// Separate paths with NUL instead of newlines (for xargs -0)
+    #[arg(short = '0', long, conflicts_with_all = ["output", "show_matches"])]
+    pub print0: bool,
+
+    /// Record fields for --output json/ndjson/csv, as a comma list (default
+    /// path,name,type,depth,size,mtime,mode); see the README for the schema
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "FIELDS")]
+    pub fields: Vec<Field>,
+
+    /// Print the parsed filter expression as a tree and exit
+    #[arg(long)]
+    pub explain: bool,
+}
+
+/// Operators have no value of their own; only their position matters, so
+/// they are added to the derived command by hand and read via indices.
+fn operators() -> [Arg; 5] {
+    let operator = |id: &'static str| {
+        Arg::new(id)
+            .long(id)
+            .action(ArgAction::Append)
+            .num_args(0)
+            .default_missing_value("true")
+    };
+
+    [
+        operator("or")
+            .short('o')
+            .help("Match if the expression on either side matches"),
+        operator("and")
+            .short('a')
+            .help("Match if the expressions on both sides match (the default)"),
+        operator("not").help("Invert the following test (also '!')"),
+        operator("lparen").hide(true),
+        operator("rparen").hide(true),
+    ]
+}
+
+/// Parses the process arguments, keeping the matches around for their indices.
+pub fn parse() -> (Args, ArgMatches) {
+    let mut cmd = Args::command().args(operators());
+    cmd.build();
+
+    let argv = rewrite_operators(&cmd, std::env::args_os().collect());
+    let matches = cmd.get_matches_from(argv);
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    (args, matches)
+}
+
+/// `!`, `(` and `)` can't be clap flags, so spell them as their long forms
+/// before parsing. Option values (`--name '('`) are left alone.
+fn rewrite_operators(cmd: &Command, argv: Vec<OsString>) -> Vec<OsString> {
+    let mut out = Vec::with_capacity(argv.len());
+    let mut value_follows = false;
+    let mut raw = false;
+
+    for (i, arg) in argv.into_iter().enumerate() {
+        if i == 0 || raw || value_follows {
+            value_follows = false;
+            out.push(arg);
+            continue;
+        }
+
+        match arg.to_str() {
+            Some("!") => out.push("--not".into()),
+            Some("(") => out.push("--lparen".into()),
+            Some(")") => out.push("--rparen".into()),
+            Some("--") => {
+                raw = true;
+                out.push(arg);
+            }
+            Some(s) => {
+                value_follows = takes_separate_value(cmd, s);
+                out.push(arg);
+            }
+            None => out.push(arg),
+        }
+    }
+
+    out
+}
+
+/// Whether `arg` is an option whose value is the next argument.
+fn takes_separate_value(cmd: &Command, arg: &str) -> bool {
+    let takes_value = |a: &Arg| {
+        a.get_action().takes_values() && a.get_num_args().is_none_or(|n| n.min_values() > 0)
+    };
+
+    if let Some(long) = arg.strip_prefix("--") {
+        return !long.contains('=')
+            && cmd
+                .get_arguments()
+                .any(|a| a.get_long() == Some(long) && takes_value(a));
+    }
+
+    if let Some(shorts) = arg.strip_prefix('-') {
+        for (i, c) in shorts.char_indices() {
+            match cmd.get_arguments().find(|a| a.get_short() == Some(c)) {
+                Some(a) if takes_value(a) => return i + c.len_utf8() == shorts.len(),
+                Some(_) => continue,
+                None => return false,
+            }
+        }
+    }
+
+    false
+}
+
+impl Args {
+    /// Walk options; the filters come from [`Args::expression`].
+    pub fn query(&self) -> Query {
+        let mut query = Query::new(&self.path)
+            .hidden(self.hidden)
+            .follow(self.follow)
+            .one_file_system(self.one_file_system)
+            .ignore(!self.no_ignore)
+            .ignore_vcs(!self.no_ignore_vcs)
+            .threads(self.threads);
+
+        for glob in &self.exclude_dir {
+            query = query.exclude_dir(glob);
+        }
+        for fs_type in &self.exclude_fstype {
+            query = query.exclude_fstype(fs_type);
+        }
+
+        if let Some(depth) = self.min_depth {
+            query = query.min_depth(depth);
+        }
+        if let Some(depth) = self.max_depth {
+            query = query.max_depth(depth);
+        }
+        if let Some(depth) = self.exact_depth {
+            query = query.exact_depth(depth);
+        }
+        if let Some(links) = self.follow_depth {
+            query = query.follow_depth(links);
+        }
+        if let Some(key) = self.sort {
+            query = query.sort(key).reverse(self.reverse);
+        }
+        if let Some(n) = self.top {
+            query = query.top(n);
+        }
+
+        query
+    }
+
+    fn case(&self) -> Case {
+        if self.ignore_case {
+            Case::Insensitive
+        } else if self.case_sensitive {
+            Case::Sensitive
+        } else {
+            Case::Smart
+        }
+    }
+
+    /// Where and how matches are written; `terminal` is whether `out` is one.
+    pub fn printer<W: Write>(&self, out: W, terminal: bool) -> Result<Printer<W>, Error> {
+        let fields = if self.fields.is_empty() {
+            Field::DEFAULT.to_vec()
+        } else {
+            self.fields.clone()
+        };
+        let mut printer = Printer::new(out, self.output, fields)
+            .print0(self.print0)
+            .long(self.long)
+            .tree(self.tree);
+
+        if let Some(template) = &self.format {
+            printer = printer.template(Template::parse(template)?);
+        }
+        if self.color.enabled(terminal) {
+            printer = printer.colors(self.colors());
+        }
+
+        Ok(if self.show_matches {
+            printer.show_matches(self.contents(&self.contains)?)
+        } else {
+            printer
+        })
+    }
+
+    /// `LS_COLORS`, highlighting what the name patterns match. Globs match
+    /// the name as a whole, so they aren't highlighted.
+    fn colors(&self) -> Colors {
+        let colors = Colors::from_env();
+
+        if self.name.is_empty() || self.glob {
+            return colors;
+        }
+
+        let case = self.case();
+        let alternatives: Vec<String> = self
+            .name
+            .iter()
+            .map(|pattern| {
+                let (pattern, escapes) = if self.regex {
+                    (pattern.clone(), true)
+                } else {
+                    (regex::escape(pattern), false)
+                };
+                if case.ignores(&pattern, escapes) {
+                    format!("(?i:{pattern})")
+                } else {
+                    format!("(?:{pattern})")
+                }
+            })
+            .collect();
+
+        match Regex::new(&alternatives.join("|")) {
+            Ok(regex) => colors.highlight(Highlight {
+                regex,
+                full_path: self.full_path,
+            }),
+            Err(_) => colors,
+        }
+    }
+
+    /// The content search for `patterns`, also used to print the matching
+    /// lines.
+    pub fn contents(&self, patterns: &[String]) -> Result<Contains, Error> {
+        let contains = if self.regex {
+            Contains::regexes(patterns, self.case())?
+        } else {
+            Contains::substrings(patterns, self.case())
+        };
+
+        Ok(match self.max_filesize {
+            Some(Bytes(max)) => contains.max_filesize(max),
+            None => contains,
+        })
+    }
+
+    /// Assembles tests and operators in the order they were given.
+    /// Consecutive occurrences of a list flag (`-e rs -e md`, `-e rs,md`)
+    /// become a single test matching any of the values.
+    pub fn expression(&self, matches: &ArgMatches) -> Result<Option<Expr>, Error> {
+        let mut items = Vec::new();
+
+        let mut table = vec![
+            (Test::Name, "name"),
+            (Test::Ext, "ext"),
+            (Test::Type, "type"),
+            (Test::Size, "size"),
+            (Test::Empty, "empty"),
+            (Test::FsType, "fstype"),
+            (Test::Contains, "contains"),
+            (Test::Mime, "mime"),
+            (Test::Kind, "kind"),
+            (Test::Within(TimeField::Modified), "changed_within"),
+            (Test::Before(TimeField::Modified), "changed_before"),
+            (Test::Newer(TimeField::Modified), "newer"),
+            (Test::Within(TimeField::Accessed), "accessed_within"),
+            (Test::Before(TimeField::Accessed), "accessed_before"),
+            (Test::Newer(TimeField::Accessed), "anewer"),
+            (Test::Within(TimeField::Changed), "ctime_within"),
+            (Test::Before(TimeField::Changed), "ctime_before"),
+            (Test::Newer(TimeField::Changed), "cnewer"),
+            (Test::Within(TimeField::Created), "created_within"),
+            (Test::Before(TimeField::Created), "created_before"),
+            (Test::Newer(TimeField::Created), "bnewer"),
+        ];
+        #[cfg(unix)]
+        table.extend([
+            (Test::Perm, "perm"),
+            (Test::User, "user"),
+            (Test::Group, "group"),
+            (Test::NoUser, "nouser"),
+            (Test::NoGroup, "nogroup"),
+            (Test::Access(Access::Readable), "readable"),
+            (Test::Access(Access::Writable), "writable"),
+            (Test::Access(Access::Executable), "executable"),
+        ]);
+
+        for (test, id) in table {
+            // Flags like --empty have an implicit default, which has an index too
+            if matches.value_source(id) != Some(ValueSource::CommandLine) {
+                continue;
+            }
+            for (n, i) in matches.indices_of(id).into_iter().flatten().enumerate() {
+                items.push((i, Item::Test(test, id, n)));
+            }
+        }
+
+        for id in ["or", "and", "not", "lparen", "rparen"] {
+            for i in matches.indices_of(id).into_iter().flatten() {
+                let token = match id {
+                    "or" => Token::Or,
+                    "and" => Token::And,
+                    "not" => Token::Not,
+                    "lparen" => Token::Open,
+                    _ => Token::Close,
+                };
+                items.push((i, Item::Op(token)));
+            }
+        }
+
+        items.sort_by_key(|&(i, _)| i);
+
+        let mut items = items.into_iter().map(|(_, item)| item).peekable();
+        let mut tokens = Vec::new();
+
+        while let Some(item) = items.next() {
+            match item {
+                Item::Op(token) => tokens.push(token),
+                Item::Test(test, id, first) => {
+                    let mut values = vec![first];
+
+                    while let Some(&Item::Test(next, _, n)) = items.peek()
+                        && next == test
+                        && test.is_list()
+                    {
+                        values.push(n);
+                        items.next();
+                    }
+
+                    tokens.push(self.test(matches, test, id, &values)?);
+                }
+            }
+        }
+
+        Expr::parse(tokens)
+    }
+
+    /// Builds one test from the given occurrences (indices into the flag's
+    /// values) of a test flag.
+    fn test(
+        &self,
+        matches: &ArgMatches,
+        test: Test,
+        id: &str,
+        values: &[usize],
+    ) -> Result<Token, Error> {
+        let pick =
+            |all: &[String]| -> Vec<String> { values.iter().map(|&n| all[n].clone()).collect() };
+
+        Ok(match test {
+            Test::Name => {
+                let patterns = pick(&self.name);
+                let matcher = if self.regex {
+                    Name::regexes(&patterns, self.case())?
+                } else if self.glob {
+                    Name::globs(&patterns, self.case())?
+                } else {
+                    Name::substrings(patterns.clone(), self.case())
+                };
+                Token::test(
+                    label("--name", &patterns),
+                    matcher.full_path(self.full_path),
+                )
+            }
+            Test::Ext => {
+                let exts = pick(&self.ext);
+                Token::test(label("--ext", &exts), Extension::any_of(exts, self.case()))
+            }
+            Test::Type => {
+                let types: Vec<FileTypeFilter> = values.iter().map(|&n| self.r#type[n]).collect();
+                let names: Vec<String> = types
+                    .iter()
+                    .map(|t| t.to_possible_value().unwrap().get_name().to_string())
+                    .collect();
+                let any = types.into_iter().fold(Or::new(), Or::with);
+                Token::test(label("--type", &names), any)
+            }
+            Test::Size => {
+                let raw = raw_values(matches, "size");
+                Token::test(format!("--size {}", raw[values[0]]), self.size[values[0]])
+            }
+            Test::Empty => Token::test("--empty", Empty),
+            Test::Contains => {
+                let patterns = pick(&self.contains);
+                Token::test(label("--contains", &patterns), self.contents(&patterns)?)
+            }
+            Test::Mime => {
+                let types = pick(&self.mime);
+                Token::test(label("--mime", &types), Mime::any_of(&types)?)
+            }
+            Test::Kind => {
+                let kinds: Vec<Kind> = values.iter().map(|&n| self.kind[n]).collect();
+                let names: Vec<String> = kinds
+                    .iter()
+                    .map(|k| k.to_possible_value().unwrap().get_name().to_string())
+                    .collect();
+                let any = kinds.into_iter().fold(Or::new(), Or::with);
+                Token::test(label("--kind", &names), any)
+            }
+            Test::FsType => {
+                let types = pick(&self.fstype);
+                Token::test(label("--fstype", &types), FsType::any_of(types))
+            }
+            Test::Within(field) | Test::Before(field) | Test::Newer(field) => {
+                let n = values[0];
+                let label = format!("--{} {}", id.replace('_', "-"), raw_values(matches, id)[n]);
+                let matcher = match test {
+                    Test::Within(_) => Time::since(field, nth::<Timestamp>(matches, id, n).0),
+                    Test::Before(_) => Time::before(field, nth::<Timestamp>(matches, id, n).0),
+                    _ => Time::newer_than(field, &nth::<PathBuf>(matches, id, n))?,
+                };
+                Token::test(label, matcher)
+            }
+            #[cfg(unix)]
+            Test::Perm => {
+                let n = values[0];
+                Token::test(
+                    format!("--perm {}", raw_values(matches, id)[n]),
+                    self.perm[n],
+                )
+            }
+            #[cfg(unix)]
+            Test::User => {
+                let user = &self.user[values[0]];
+                Token::test(format!("--user {user}"), Owner::user(user)?)
+            }
+            #[cfg(unix)]
+            Test::Group => {
+                let group = &self.group[values[0]];
+                Token::test(format!("--group {group}"), Owner::group(group)?)
+            }
+            #[cfg(unix)]
+            Test::NoUser => Token::test("--nouser", Owner::NoUser),
+            #[cfg(unix)]
+            Test::NoGroup => Token::test("--nogroup", Owner::NoGroup),
+            #[cfg(unix)]
+            Test::Access(access) => Token::test(format!("--{id}"), access),
+        })
+    }
+}
+
+/// Test flags, in the order [`Args::expression`] collects them.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Test {
+    Name,
+    Ext,
+    Type,
+    Size,
+    Empty,
+    FsType,
+    Contains,
+    Mime,
+    Kind,
+    Within(TimeField),
+    Before(TimeField),
+    Newer(TimeField),
+    #[cfg(unix)]
+    Perm,
+    #[cfg(unix)]
+    User,
+    #[cfg(unix)]
+    Group,
+    #[cfg(unix)]
+    NoUser,
+    #[cfg(unix)]
+    NoGroup,
+    #[cfg(unix)]
+    Access(Access),
+}
+
+impl Test {
+    /// Whether back to back occurrences merge into one "any of" test.
+    /// Others are ANDed like any other adjacent tests, so that e.g.
+    /// `--size +1M --size -10M` means between the two.
+    fn is_list(self) -> bool {
+        matches!(
+            self,
+            Test::Name
+                | Test::Ext
+                | Test::Type
+                | Test::FsType
+                | Test::Contains
+                | Test::Mime
+                | Test::Kind
+        )
+    }
+}
+
+enum Item {
+    /// The n-th value of a test flag, with the flag's id.
+    Test(Test, &'static str, usize),
+    Op(Token),
+}
+
+/// The n-th parsed value of `id`.
+fn nth<T: Clone + Send + Sync + 'static>(matches: &ArgMatches, id: &str, n: usize) -> T {
+    matches
+        .get_many::<T>(id)
+        .and_then(|mut values| values.nth(n))
+        .cloned()
+        .expect("index comes from the same matches")
+}
+
+/// The values of `id` as typed, for labels.
+fn raw_values(matches: &ArgMatches, id: &str) -> Vec<String> {
+    matches
+        .get_raw(id)
+        .into_iter()
+        .flatten()
+        .map(|value| value.to_string_lossy().into_owned())
+        .collect()
+}
+
+fn label(flag: &str, values: &[String]) -> String {
+    match values {
+        [value] => format!("{flag} {value}"),
+        _ => format!("{flag} any of {}", values.join(", ")),
+    }
+}
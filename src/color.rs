@@ -0,0 +1,264 @@
+//! Coloring paths in plain output, `ls`-style.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::ops::Range;
+
+use clap::ValueEnum;
+use lookfor::Match;
+use regex::bytes::Regex;
+
+/// `--color` choices.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Used when `LS_COLORS` is unset or empty; a subset of the GNU defaults.
+const DEFAULT_COLORS: &str = "di=01;34:ln=01;36:or=40;31;01:so=01;35:pi=40;33:bd=40;33;01:\
+cd=40;33;01:ex=01;32:*.tar=01;31:*.tgz=01;31:*.gz=01;31:*.bz2=01;31:*.xz=01;31:*.zst=01;31:\
+*.zip=01;31:*.7z=01;31:*.rar=01;31:*.jpg=01;35:*.jpeg=01;35:*.png=01;35:*.gif=01;35:\
+*.svg=01;35:*.webp=01;35:*.mp4=01;35:*.mkv=01;35:*.mp3=00;36:*.flac=00;36:*.wav=00;36";
+
+/// Matched parts of the name, like grep's default.
+const HIGHLIGHT: &str = "01;31";
+
+/// Colors by entry type and extension, from `LS_COLORS`.
+#[derive(Clone, Debug)]
+pub struct Colors {
+    /// Two-letter type keys (`di`, `ln`, `ex`, ...).
+    types: HashMap<String, String>,
+    /// `*suffix` keys, lowercased, longest first.
+    suffixes: Vec<(String, String)>,
+    highlight: Option<Highlight>,
+}
+
+/// What to highlight in each path: the spans `regex` matches in the name,
+/// or in the path below the root with `full_path`.
+#[derive(Clone, Debug)]
+pub struct Highlight {
+    pub regex: Regex,
+    pub full_path: bool,
+}
+
+impl ColorChoice {
+    /// Whether to color, given whether stdout is a terminal.
+    pub fn enabled(self, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => terminal && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+        }
+    }
+}
+
+impl Colors {
+    pub fn from_env() -> Self {
+        match env::var("LS_COLORS") {
+            Ok(spec) if !spec.is_empty() => Colors::parse(&spec),
+            _ => Colors::parse(DEFAULT_COLORS),
+        }
+    }
+
+    fn parse(spec: &str) -> Self {
+        let mut types = HashMap::new();
+        let mut suffixes = Vec::new();
+
+        for (key, code) in spec.split(':').filter_map(|entry| entry.split_once('=')) {
+            match key.strip_prefix('*') {
+                Some(suffix) => suffixes.push((suffix.to_lowercase(), code.to_string())),
+                None => {
+                    types.insert(key.to_string(), code.to_string());
+                }
+            }
+        }
+
+        // The most specific suffix wins, e.g. `.tar.gz` over `.gz`
+        suffixes.sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+
+        Colors {
+            types,
+            suffixes,
+            highlight: None,
+        }
+    }
+
+    pub fn highlight(mut self, highlight: Highlight) -> Self {
+        self.highlight = Some(highlight);
+        self
+    }
+
+    /// Appends the colored path of `m` to `out`: parent directories in the
+    /// directory color, the name by its type, and highlighted matches.
+    pub fn paint(&self, m: &Match, out: &mut Vec<u8>) {
+        self.paint_tail(m, m.path().as_os_str().as_encoded_bytes(), out);
+    }
+
+    /// Like [`Colors::paint`], but just the name of `m`.
+    pub fn paint_name(&self, m: &Match, out: &mut Vec<u8>) {
+        self.paint_tail(m, m.file_name().as_encoded_bytes(), out);
+    }
+
+    /// Appends `name` in the directory color, for directories that aren't
+    /// matches themselves.
+    pub fn paint_dir(&self, name: &[u8], out: &mut Vec<u8>) {
+        match self.type_code("di") {
+            Some(code) => {
+                out.extend_from_slice(format!("\x1b[{code}m").as_bytes());
+                out.extend_from_slice(name);
+                out.extend_from_slice(b"\x1b[0m");
+            }
+            None => out.extend_from_slice(name),
+        }
+    }
+
+    /// Paints `path`, which ends with the name of `m`.
+    fn paint_tail(&self, m: &Match, path: &[u8], out: &mut Vec<u8>) {
+        let name_len = m.file_name().as_encoded_bytes().len();
+        let name_start = if path.ends_with(m.file_name().as_encoded_bytes()) {
+            path.len() - name_len
+        } else {
+            0
+        };
+
+        let dir = self.types.get("di").map(String::as_str);
+        let name = self.code(m);
+        let spans = self.spans(m, path);
+
+        let style = |i: usize| {
+            if spans.iter().any(|span| span.contains(&i)) {
+                Some(HIGHLIGHT)
+            } else if i < name_start {
+                dir
+            } else {
+                name
+            }
+        };
+
+        let mut start = 0;
+        while start < path.len() {
+            let current = style(start);
+            let end = (start..path.len())
+                .find(|&i| style(i) != current)
+                .unwrap_or(path.len());
+
+            match current {
+                Some(code) => {
+                    out.extend_from_slice(format!("\x1b[{code}m").as_bytes());
+                    out.extend_from_slice(&path[start..end]);
+                    out.extend_from_slice(b"\x1b[0m");
+                }
+                None => out.extend_from_slice(&path[start..end]),
+            }
+            start = end;
+        }
+    }
+
+    /// The code for the entry's name, following `ls`: special types first,
+    /// then executables, then extensions.
+    fn code(&self, m: &Match) -> Option<&str> {
+        let file_type = m.file_type();
+
+        let key = if file_type.is_dir() {
+            "di"
+        } else if file_type.is_symlink() {
+            if fs::metadata(m.path()).is_err() {
+                "or"
+            } else {
+                "ln"
+            }
+        } else if file_type.is_file() {
+            if is_executable(m) {
+                "ex"
+            } else {
+                return self.suffix_code(m).or_else(|| self.type_code("fi"));
+            }
+        } else {
+            special_key(file_type)
+        };
+
+        self.type_code(key)
+    }
+
+    fn type_code(&self, key: &str) -> Option<&str> {
+        self.types
+            .get(key)
+            .map(String::as_str)
+            .filter(|code| !code.is_empty())
+    }
+
+    fn suffix_code(&self, m: &Match) -> Option<&str> {
+        let name = m.file_name().to_string_lossy().to_lowercase();
+
+        self.suffixes
+            .iter()
+            .find(|(suffix, _)| name.ends_with(suffix.as_str()))
+            .map(|(_, code)| code.as_str())
+    }
+
+    /// Byte ranges of `path` to highlight.
+    fn spans(&self, m: &Match, path: &[u8]) -> Vec<Range<usize>> {
+        let Some(highlight) = &self.highlight else {
+            return Vec::new();
+        };
+
+        let subject = if highlight.full_path {
+            m.entry().relative_path().as_os_str()
+        } else {
+            m.file_name()
+        };
+        let subject = subject.as_encoded_bytes();
+
+        // The subject is the tail of the printed path
+        let Some(offset) = path.len().checked_sub(subject.len()) else {
+            return Vec::new();
+        };
+
+        highlight
+            .regex
+            .find_iter(subject)
+            .filter(|found| !found.is_empty())
+            .map(|found| found.start() + offset..found.end() + offset)
+            .collect()
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(m: &Match) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    m.metadata()
+        .is_some_and(|md| md.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_: &Match) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn special_key(file_type: fs::FileType) -> &'static str {
+    use std::os::unix::fs::FileTypeExt;
+
+    if file_type.is_socket() {
+        "so"
+    } else if file_type.is_fifo() {
+        "pi"
+    } else if file_type.is_block_device() {
+        "bd"
+    } else if file_type.is_char_device() {
+        "cd"
+    } else {
+        "no"
+    }
+}
+
+#[cfg(not(unix))]
+fn special_key(_: fs::FileType) -> &'static str {
+    "no"
+}
@@ -0,0 +1,101 @@
+use std::cell::OnceCell;
+use std::ffi::OsStr;
+use std::fs::{FileType, Metadata};
+use std::path::Path;
+
+use walkdir::DirEntry;
+
+use crate::magic;
+
+/// A directory entry as seen by a [`Matcher`](crate::Matcher).
+///
+/// Metadata is only fetched the first time something asks for it, so
+/// matchers that look at names alone never cost a `stat` call.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    inner: DirEntry,
+    depth: usize,
+    metadata: OnceCell<Option<Metadata>>,
+    mime: OnceCell<Option<&'static str>>,
+}
+
+impl Entry {
+    /// `depth` is below the query root, which is not necessarily where
+    /// `inner` was walked from.
+    pub(crate) fn new(inner: DirEntry, depth: usize) -> Self {
+        Entry {
+            inner,
+            depth,
+            metadata: OnceCell::new(),
+            mime: OnceCell::new(),
+        }
+    }
+
+    /// Full path, starting with the query root.
+    pub fn path(&self) -> &Path {
+        self.inner.path()
+    }
+
+    /// Final path component (the root itself when it has none, e.g. `.`).
+    pub fn file_name(&self) -> &OsStr {
+        self.inner.file_name()
+    }
+
+    /// Path below the query root, e.g. `src/lib.rs` (empty for the root).
+    pub fn relative_path(&self) -> &Path {
+        let mut components = self.path().components();
+
+        for _ in self.depth..components.clone().count() {
+            components.next();
+        }
+
+        components.as_path()
+    }
+
+    /// Depth below the query root (the root is 0).
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Type of the entry; symlinks are only resolved with
+    /// [`Query::follow`](crate::Query::follow).
+    pub fn file_type(&self) -> FileType {
+        self.inner.file_type()
+    }
+
+    /// Metadata of the entry, or `None` if it could not be read.
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata
+            .get_or_init(|| self.inner.metadata().ok())
+            .as_ref()
+    }
+
+    /// MIME type detected from the first bytes of a regular file (e.g.
+    /// `image/png`, `text/x-python`), or `inode/directory` and
+    /// `inode/symlink` for those. `None` for other entries and unreadable
+    /// files. Like the metadata, it is only sniffed once.
+    pub fn mime(&self) -> Option<&'static str> {
+        *self.mime.get_or_init(|| {
+            let file_type = self.file_type();
+
+            if file_type.is_file() {
+                magic::sniff(self.path()).ok()
+            } else if file_type.is_dir() {
+                Some("inode/directory")
+            } else if file_type.is_symlink() {
+                Some("inode/symlink")
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The underlying `walkdir` entry.
+    pub fn dir_entry(&self) -> &DirEntry {
+        &self.inner
+    }
+
+    pub(crate) fn into_dir_entry(self) -> DirEntry {
+        self.inner
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Errors produced while building or running a search.
+#[derive(Debug)]
+pub enum Error {
+    /// A name pattern could not be compiled as a regular expression.
+    Regex {
+        pattern: String,
+        source: regex::Error,
+    },
+    /// A glob pattern could not be compiled.
+    Glob {
+        pattern: String,
+        source: globset::Error,
+    },
+    /// A test argument (size, time, mode, ...) could not be parsed.
+    Value {
+        kind: &'static str,
+        value: String,
+        reason: String,
+    },
+    /// A filter expression is malformed (unbalanced parentheses, dangling operator, ...).
+    Expr(String),
+    /// A followed symlink leads back to a directory the walk is already in.
+    Loop { path: PathBuf, ancestor: PathBuf },
+    /// The walk could not read an entry (permission denied, vanished file, ...).
+    Walk(walkdir::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Regex { pattern, source } => write!(f, "Invalid regex '{pattern}': {source}"),
+            // globset already quotes the offending pattern
+            Error::Glob { source, .. } => write!(f, "Invalid glob: {source}"),
+            Error::Value {
+                kind,
+                value,
+                reason,
+            } => write!(f, "Invalid {kind} '{value}': {reason}"),
+            Error::Expr(message) => write!(f, "Invalid expression: {message}"),
+            Error::Loop { path, ancestor } => write!(
+                f,
+                "File system loop: {} leads back to {}",
+                path.display(),
+                ancestor.display()
+            ),
+            Error::Walk(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Regex { source, .. } => Some(source),
+            Error::Glob { source, .. } => Some(source),
+            Error::Value { .. } | Error::Expr(_) | Error::Loop { .. } => None,
+            Error::Walk(e) => Some(e),
+        }
+    }
+}
+
+impl From<walkdir::Error> for Error {
+    fn from(e: walkdir::Error) -> Self {
+        // Loops get their own variant so both walkers report them alike
+        match (e.path(), e.loop_ancestor()) {
+            (Some(path), Some(ancestor)) => Error::Loop {
+                path: path.to_path_buf(),
+                ancestor: ancestor.to_path_buf(),
+            },
+            _ => Error::Walk(e),
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! find-style filter expressions: tests combined with AND, OR, NOT and
+//! parentheses.
+//!
+//! Precedence from tightest to loosest is NOT, AND, OR. Two tests next to
+//! each other without an operator are ANDed, so `a b -o c` reads as
+//! `(a AND b) OR c`.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::Error;
+use crate::matcher::{And, Matcher, Not, Or};
+
+/// One element of an expression, in the order it appeared.
+pub enum Token {
+    /// A test, labelled with how the user spelled it (e.g. `--name foo`).
+    Test(String, Arc<dyn Matcher>),
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+impl Token {
+    pub fn test(label: impl Into<String>, matcher: impl Matcher + 'static) -> Self {
+        Token::Test(label.into(), Arc::new(matcher))
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Token::Test(label, _) => format!("'{label}'"),
+            Token::Not => "'!'".to_string(),
+            Token::And => "'-a'".to_string(),
+            Token::Or => "'-o'".to_string(),
+            Token::Open => "'('".to_string(),
+            Token::Close => "')'".to_string(),
+        }
+    }
+}
+
+/// A parsed expression tree.
+#[derive(Clone)]
+pub enum Expr {
+    Test(String, Arc<dyn Matcher>),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    /// Parses `tokens`; an empty token list yields `None` (match everything).
+    pub fn parse(tokens: impl IntoIterator<Item = Token>) -> Result<Option<Expr>, Error> {
+        let mut parser = Parser {
+            tokens: tokens
+                .into_iter()
+                .collect::<Vec<_>>()
+                .into_iter()
+                .peekable(),
+        };
+
+        if parser.tokens.peek().is_none() {
+            return Ok(None);
+        }
+
+        let expr = parser.or()?;
+
+        match parser.tokens.next() {
+            None => Ok(Some(expr)),
+            Some(Token::Close) => Err(syntax("unmatched ')'")),
+            Some(token) => Err(syntax(format!("unexpected {}", token.describe()))),
+        }
+    }
+
+    /// Builds the matcher that evaluates this tree.
+    pub fn into_matcher(self) -> Arc<dyn Matcher> {
+        match self {
+            Expr::Test(_, matcher) => matcher,
+            Expr::Not(inner) => Arc::new(Not(inner.into_matcher())),
+            Expr::And(children) => Arc::new(
+                children
+                    .into_iter()
+                    .fold(And::new(), |all, e| all.with(e.into_matcher())),
+            ),
+            Expr::Or(children) => Arc::new(
+                children
+                    .into_iter()
+                    .fold(Or::new(), |any, e| any.with(e.into_matcher())),
+            ),
+        }
+    }
+
+    fn label(&self) -> &str {
+        match self {
+            Expr::Test(label, _) => label,
+            Expr::Not(_) => "not",
+            Expr::And(_) => "and",
+            Expr::Or(_) => "or",
+        }
+    }
+
+    fn children(&self) -> &[Expr] {
+        match self {
+            Expr::Test(..) => &[],
+            Expr::Not(inner) => std::slice::from_ref(inner),
+            Expr::And(children) | Expr::Or(children) => children,
+        }
+    }
+
+    fn fmt_tree(&self, f: &mut fmt::Formatter<'_>, prefix: &str) -> fmt::Result {
+        let children = self.children();
+
+        for (i, child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let (branch, indent) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+
+            writeln!(f, "{prefix}{branch}{}", child.label())?;
+            child.fmt_tree(f, &format!("{prefix}{indent}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders the tree one node per line, for `--explain`.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.label())?;
+        self.fmt_tree(f, "")
+    }
+}
+
+impl fmt::Debug for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Test(label, _) => f.debug_tuple("Test").field(label).finish(),
+            Expr::Not(inner) => f.debug_tuple("Not").field(inner).finish(),
+            Expr::And(children) => f.debug_tuple("And").field(children).finish(),
+            Expr::Or(children) => f.debug_tuple("Or").field(children).finish(),
+        }
+    }
+}
+
+struct Parser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl Parser {
+    // or := and ('-o' and)*
+    fn or(&mut self) -> Result<Expr, Error> {
+        let mut children = vec![self.and()?];
+
+        while matches!(self.tokens.peek(), Some(Token::Or)) {
+            self.tokens.next();
+            children.push(self.and()?);
+        }
+
+        Ok(collapse(children, Expr::Or))
+    }
+
+    // and := unary (['-a'] unary)*
+    fn and(&mut self) -> Result<Expr, Error> {
+        let mut children = vec![self.unary()?];
+
+        loop {
+            match self.tokens.peek() {
+                Some(Token::And) => {
+                    self.tokens.next();
+                }
+                Some(Token::Test(..) | Token::Not | Token::Open) => {}
+                _ => break,
+            }
+            children.push(self.unary()?);
+        }
+
+        Ok(collapse(children, Expr::And))
+    }
+
+    // unary := '!' unary | '(' or ')' | test
+    fn unary(&mut self) -> Result<Expr, Error> {
+        match self.tokens.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                if matches!(self.tokens.peek(), Some(Token::Close)) {
+                    return Err(syntax("empty parentheses"));
+                }
+
+                let inner = self.or()?;
+
+                match self.tokens.next() {
+                    Some(Token::Close) => Ok(inner),
+                    _ => Err(syntax("unmatched '('")),
+                }
+            }
+            Some(Token::Test(label, matcher)) => Ok(Expr::Test(label, matcher)),
+            Some(token) => Err(syntax(format!(
+                "expected a test, found {}",
+                token.describe()
+            ))),
+            None => Err(syntax("expected a test at the end of the expression")),
+        }
+    }
+}
+
+/// A single operand is returned as-is rather than wrapped in a one-child node.
+fn collapse(mut children: Vec<Expr>, node: fn(Vec<Expr>) -> Expr) -> Expr {
+    if children.len() == 1 {
+        children.pop().unwrap()
+    } else {
+        node(children)
+    }
+}
+
+fn syntax(message: impl Into<String>) -> Error {
+    Error::Expr(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::Entry;
+
+    struct Always;
+
+    impl Matcher for Always {
+        fn is_match(&self, _: &Entry) -> bool {
+            true
+        }
+    }
+
+    /// Parses space-separated tokens, where anything but an operator is a
+    /// test labelled with itself, and returns the tree in `Debug` form.
+    fn parse(expr: &str) -> Result<String, String> {
+        let tokens = expr.split_whitespace().map(|word| match word {
+            "!" => Token::Not,
+            "-a" => Token::And,
+            "-o" => Token::Or,
+            "(" => Token::Open,
+            ")" => Token::Close,
+            test => Token::test(test, Always),
+        });
+
+        match Expr::parse(tokens) {
+            Ok(expr) => Ok(format!("{expr:?}")),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    #[test]
+    fn empty_matches_everything() {
+        assert_eq!(parse(""), Ok("None".to_string()));
+    }
+
+    #[test]
+    fn single_test_is_not_wrapped() {
+        assert_eq!(parse("a"), Ok(r#"Some(Test("a"))"#.to_string()));
+    }
+
+    #[test]
+    fn adjacent_tests_are_anded() {
+        assert_eq!(parse("a b"), parse("a -a b"));
+        assert_eq!(
+            parse("a b c"),
+            Ok(r#"Some(And([Test("a"), Test("b"), Test("c")]))"#.to_string())
+        );
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse("a b -o c"),
+            Ok(r#"Some(Or([And([Test("a"), Test("b")]), Test("c")]))"#.to_string())
+        );
+        assert_eq!(
+            parse("a -o b -a c"),
+            Ok(r#"Some(Or([Test("a"), And([Test("b"), Test("c")])]))"#.to_string())
+        );
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and() {
+        assert_eq!(
+            parse("! a b"),
+            Ok(r#"Some(And([Not(Test("a")), Test("b")]))"#.to_string())
+        );
+        assert_eq!(
+            parse("! ! a"),
+            Ok(r#"Some(Not(Not(Test("a"))))"#.to_string())
+        );
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(
+            parse("( a -o b ) c"),
+            Ok(r#"Some(And([Or([Test("a"), Test("b")]), Test("c")]))"#.to_string())
+        );
+        assert_eq!(
+            parse("! ( a -o b )"),
+            Ok(r#"Some(Not(Or([Test("a"), Test("b")])))"#.to_string())
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = |message: &str| Err(format!("Invalid expression: {message}"));
+
+        assert_eq!(parse("( a"), error("unmatched '('"));
+        assert_eq!(parse("a )"), error("unmatched ')'"));
+        assert_eq!(parse("( )"), error("empty parentheses"));
+        assert_eq!(
+            parse("a -o"),
+            error("expected a test at the end of the expression")
+        );
+        assert_eq!(
+            parse("a !"),
+            error("expected a test at the end of the expression")
+        );
+        assert_eq!(parse("-o a"), error("expected a test, found '-o'"));
+        assert_eq!(parse("a -a -o b"), error("expected a test, found '-o'"));
+    }
+}
@@ -0,0 +1,65 @@
+//! Walking through symlinked directories (`Query::follow`).
+
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use same_file::Handle;
+use walkdir::{DirEntry, WalkDir};
+
+/// The directories on the path from the root to the one being read, kept
+/// open so a symlink leading back up can be recognized, like `walkdir` does
+/// for the sequential walk.
+pub(crate) struct Ancestors {
+    handle: Handle,
+    path: PathBuf,
+    parent: Option<Arc<Ancestors>>,
+}
+
+impl Ancestors {
+    /// Adds `dir` below `parent`.
+    pub(crate) fn push(parent: Option<Arc<Ancestors>>, dir: &Path) -> io::Result<Arc<Self>> {
+        Ok(Arc::new(Ancestors {
+            handle: Handle::from_path(dir)?,
+            path: dir.to_path_buf(),
+            parent,
+        }))
+    }
+
+    /// The ancestor `dir` leads back to, if any.
+    pub(crate) fn find(&self, dir: &Path) -> Option<&Path> {
+        let handle = Handle::from_path(dir).ok()?;
+        let mut node = Some(self);
+
+        while let Some(ancestor) = node {
+            if ancestor.handle == handle {
+                return Some(&ancestor.path);
+            }
+            node = ancestor.parent.as_deref();
+        }
+
+        None
+    }
+}
+
+/// When following links, `walkdir` reports a symlink whose target is gone
+/// as an error. Such links are still entries of their directory, so this
+/// recovers one for the link itself.
+pub(crate) fn broken_link(error: &walkdir::Error) -> Option<DirEntry> {
+    if error.io_error()?.kind() != ErrorKind::NotFound {
+        return None;
+    }
+
+    let path = error.path()?;
+    if !fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()) {
+        return None;
+    }
+
+    WalkDir::new(path)
+        .follow_root_links(false)
+        .max_depth(0)
+        .into_iter()
+        .next()?
+        .ok()
+}
@@ -0,0 +1,131 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder, gitconfig_excludes_path};
+
+/// Per-directory ignore files, highest precedence first.
+const IGNORE_FILES: [&str; 2] = [".lookforignore", ".ignore"];
+
+/// Ignore rules in effect inside one directory.
+///
+/// Each directory links to its parent's rules, so a walk only reads the
+/// ignore files of a directory once and nested `.gitignore`s override the
+/// ones above them. All paths are absolute so rules loaded from above the
+/// search root still line up with the walked entries.
+#[derive(Debug)]
+pub(crate) struct Ignore {
+    parent: Option<Arc<Ignore>>,
+    /// Rules from files in this directory, highest precedence first.
+    rules: Vec<Gitignore>,
+    /// `.git/info/exclude` and `core.excludesFile` of the enclosing
+    /// repository, consulted after every per-directory file. `None` outside
+    /// a repository (or with VCS rules disabled), which also turns off
+    /// `.gitignore` files.
+    repo: Option<Arc<Vec<Gitignore>>>,
+    vcs: bool,
+}
+
+impl Ignore {
+    /// Rules for the (absolute) search root, including those inherited from
+    /// its parent directories up to the enclosing repository.
+    pub(crate) fn root(root: &Path, vcs: bool) -> Arc<Ignore> {
+        let ancestors: Vec<&Path> = root.ancestors().collect();
+        let repo_root = ancestors
+            .iter()
+            .position(|dir| vcs && dir.join(".git").exists());
+
+        let mut rules = None;
+
+        // Directories between the repository root and the search root
+        // contribute their ignore files too.
+        if let Some(top) = repo_root {
+            for dir in ancestors[1..=top].iter().rev() {
+                rules = Some(Ignore::load(rules, dir, vcs));
+            }
+        }
+
+        Ignore::load(rules, root, vcs)
+    }
+
+    /// Rules for `dir`, a direct child of the directory these rules are for.
+    pub(crate) fn child(self: &Arc<Self>, dir: &Path) -> Arc<Ignore> {
+        Ignore::load(Some(self.clone()), dir, self.vcs)
+    }
+
+    fn load(parent: Option<Arc<Ignore>>, dir: &Path, vcs: bool) -> Arc<Ignore> {
+        let repo = if vcs && dir.join(".git").exists() {
+            Some(Arc::new(repo_rules(dir)))
+        } else {
+            parent.as_ref().and_then(|p| p.repo.clone())
+        };
+
+        let mut names = IGNORE_FILES.to_vec();
+        if repo.is_some() {
+            names.push(".gitignore");
+        }
+
+        let rules = names
+            .into_iter()
+            .map(|name| dir.join(name))
+            .filter(|path| path.is_file())
+            .filter_map(|path| build(dir, &path))
+            .collect();
+
+        Arc::new(Ignore {
+            parent,
+            rules,
+            repo,
+            vcs,
+        })
+    }
+
+    /// Whether `path`, an entry of the directory these rules are for, is
+    /// ignored. The nearest matching rule wins, so `!pattern` in a nested
+    /// file re-includes what a parent excluded.
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut node = Some(self);
+
+        while let Some(ignore) = node {
+            if let Some(ignored) = decide(&ignore.rules, path, is_dir) {
+                return ignored;
+            }
+            node = ignore.parent.as_deref();
+        }
+
+        self.repo
+            .as_deref()
+            .and_then(|rules| decide(rules, path, is_dir))
+            .unwrap_or(false)
+    }
+}
+
+fn decide(rules: &[Gitignore], path: &Path, is_dir: bool) -> Option<bool> {
+    rules
+        .iter()
+        .find_map(|rules| match rules.matched(path, is_dir) {
+            Match::Ignore(_) => Some(true),
+            Match::Whitelist(_) => Some(false),
+            Match::None => None,
+        })
+}
+
+/// `.git/info/exclude` first, then the user's global `core.excludesFile`.
+fn repo_rules(repo: &Path) -> Vec<Gitignore> {
+    let exclude = repo.join(".git").join("info").join("exclude");
+
+    [Some(exclude), gitconfig_excludes_path()]
+        .into_iter()
+        .flatten()
+        .filter(|path| path.is_file())
+        .filter_map(|path| build(repo, &path))
+        .collect()
+}
+
+/// Unreadable or malformed ignore files are skipped rather than failing the
+/// search, like git does.
+fn build(root: &Path, file: &Path) -> Option<Gitignore> {
+    let mut builder = GitignoreBuilder::new(root);
+    builder.add(file);
+    builder.build().ok().filter(|rules| !rules.is_empty())
+}
@@ -0,0 +1,25 @@
+//! The search engine behind the `lookfor` command line tool.
+//!
+//! Build a [`Query`], call [`Query::search`] and iterate over the [`Match`]es.
+//! Custom predicates plug in through the [`Matcher`] trait.
+
+mod entry;
+mod error;
+pub mod expr;
+mod follow;
+mod ignores;
+mod magic;
+pub mod matcher;
+mod mounts;
+mod parallel;
+mod prune;
+mod query;
+mod sort;
+#[cfg(unix)]
+pub mod users;
+
+pub use entry::Entry;
+pub use error::Error;
+pub use matcher::Matcher;
+pub use query::{FileTypeFilter, Match, Query, Search};
+pub use sort::SortKey;
@@ -0,0 +1,182 @@
+//! `-l/--long` listings, aligned like `ls -l`.
+//!
+//! Columns come from [`Match::metadata`], which is fetched at most once per
+//! entry, so size and time tests that already looked at it cost nothing
+//! extra here.
+
+use std::fs::{self, FileType, Metadata};
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local};
+use lookfor::Match;
+
+/// Times further back than this show the year instead of the time of day.
+const RECENT: Duration = Duration::from_secs(182 * 24 * 60 * 60);
+
+/// Rows are collected until the end, since column widths depend on all of them.
+#[derive(Default)]
+pub struct Listing {
+    rows: Vec<Row>,
+}
+
+struct Row {
+    /// Mode, links, user, group, size and mtime.
+    cells: [String; 6],
+    /// The (possibly colored) path, plus ` -> target` for symlinks.
+    path: Vec<u8>,
+}
+
+impl Listing {
+    /// Adds `m`, printed as `path`.
+    pub fn push(&mut self, m: &Match, mut path: Vec<u8>) {
+        let metadata = m.metadata();
+
+        if m.file_type().is_symlink()
+            && let Ok(target) = fs::read_link(m.path())
+        {
+            path.extend_from_slice(b" -> ");
+            path.extend_from_slice(target.as_os_str().as_encoded_bytes());
+        }
+
+        let cells = match metadata {
+            Some(md) => {
+                let (user, group) = owner(md);
+                [
+                    mode(m.file_type(), md),
+                    links(md),
+                    user,
+                    group,
+                    md.len().to_string(),
+                    md.modified().map(mtime).unwrap_or_default(),
+                ]
+            }
+            None => std::array::from_fn(|_| "?".to_string()),
+        };
+
+        self.rows.push(Row { cells, path });
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut widths = [0; 6];
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(&row.cells) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        for Row { cells, path } in &self.rows {
+            let [mode, links, user, group, size, mtime] = cells;
+            let [w_mode, w_links, w_user, w_group, w_size, w_mtime] = widths;
+
+            write!(
+                out,
+                "{mode:<w_mode$} {links:>w_links$} {user:<w_user$} {group:<w_group$} \
+                 {size:>w_size$} {mtime:<w_mtime$} "
+            )?;
+            out.write_all(path)?;
+            out.write_all(b"\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `ls`-style date: `Oct 17 01:56` for recent times, `Oct 17  2023` otherwise.
+fn mtime(time: SystemTime) -> String {
+    let recent = SystemTime::now()
+        .duration_since(time)
+        .is_ok_and(|age| age < RECENT);
+    let format = if recent { "%b %e %H:%M" } else { "%b %e  %Y" };
+
+    DateTime::<Local>::from(time).format(format).to_string()
+}
+
+#[cfg(unix)]
+fn mode(file_type: FileType, metadata: &Metadata) -> String {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    let kind = if file_type.is_dir() {
+        'd'
+    } else if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_fifo() {
+        'p'
+    } else if file_type.is_socket() {
+        's'
+    } else if file_type.is_block_device() {
+        'b'
+    } else if file_type.is_char_device() {
+        'c'
+    } else {
+        '-'
+    };
+
+    let mode = metadata.permissions().mode();
+    let bit = |mask: u32, c: char| if mode & mask != 0 { c } else { '-' };
+    // The execute slot also shows setuid/setgid/sticky, capitalized when the
+    // execute bit itself is off
+    let special = |x: u32, s: u32, on: char| match (mode & x != 0, mode & s != 0) {
+        (true, true) => on,
+        (false, true) => on.to_ascii_uppercase(),
+        (true, false) => 'x',
+        (false, false) => '-',
+    };
+
+    [
+        kind,
+        bit(0o400, 'r'),
+        bit(0o200, 'w'),
+        special(0o100, 0o4000, 's'),
+        bit(0o040, 'r'),
+        bit(0o020, 'w'),
+        special(0o010, 0o2000, 's'),
+        bit(0o004, 'r'),
+        bit(0o002, 'w'),
+        special(0o001, 0o1000, 't'),
+    ]
+    .into_iter()
+    .collect()
+}
+
+#[cfg(not(unix))]
+fn mode(file_type: FileType, metadata: &Metadata) -> String {
+    let kind = if file_type.is_dir() { 'd' } else { '-' };
+    let write = if metadata.permissions().readonly() {
+        '-'
+    } else {
+        'w'
+    };
+
+    format!("{kind}r{write}")
+}
+
+#[cfg(unix)]
+fn links(metadata: &Metadata) -> String {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.nlink().to_string()
+}
+
+#[cfg(not(unix))]
+fn links(_: &Metadata) -> String {
+    "1".to_string()
+}
+
+/// User and group names, or the numeric ids when they have none.
+#[cfg(unix)]
+fn owner(metadata: &Metadata) -> (String, String) {
+    use std::os::unix::fs::MetadataExt;
+
+    let (uid, gid) = (metadata.uid(), metadata.gid());
+
+    (
+        lookfor::users::user_name(uid).unwrap_or_else(|| uid.to_string()),
+        lookfor::users::group_name(gid).unwrap_or_else(|| gid.to_string()),
+    )
+}
+
+#[cfg(not(unix))]
+fn owner(_: &Metadata) -> (String, String) {
+    ("-".to_string(), "-".to_string())
+}
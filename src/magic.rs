@@ -0,0 +1,316 @@
+//! Telling file types apart by their first bytes rather than their names.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Enough for every signature below; `ustar` sits at offset 257.
+const SNIFF_LEN: u64 = 1024;
+
+/// Signatures as (offset, bytes, MIME type), checked in order. Each is long
+/// or odd enough that other files don't start with it by accident.
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"II*\0", "image/tiff"),
+    (0, b"MM\0*", "image/tiff"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"PK\x05\x06", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"\xfd7zXZ\0", "application/x-xz"),
+    (0, b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (0, b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (0, b"Rar!\x1a\x07", "application/vnd.rar"),
+    (257, b"ustar", "application/x-tar"),
+    (0, b"\x7fELF", "application/x-elf"),
+    (0, b"\xfe\xed\xfa\xce", "application/x-mach-binary"),
+    (0, b"\xfe\xed\xfa\xcf", "application/x-mach-binary"),
+    (0, b"\xce\xfa\xed\xfe", "application/x-mach-binary"),
+    (0, b"\xcf\xfa\xed\xfe", "application/x-mach-binary"),
+    (0, b"\0asm", "application/wasm"),
+    (0, b"SQLite format 3\0", "application/vnd.sqlite3"),
+];
+
+type HeaderCheck = fn(&[u8]) -> bool;
+
+/// Short or unanchored signatures as (leading bytes, header check, MIME
+/// type), only trusted if the rest of the header checks out: plenty of text
+/// starts with `BM` or `MZ`, or has `ftyp` as its fifth to eighth bytes.
+/// ISO media files start with a box size rather than fixed bytes.
+const CHECKED: &[(&[u8], HeaderCheck, &str)] = &[
+    (b"\0\0\x01\0", is_ico, "image/x-icon"),
+    (b"BM", is_bmp, "image/bmp"),
+    (
+        b"MZ",
+        is_pe,
+        "application/vnd.microsoft.portable-executable",
+    ),
+    (
+        b"RIFF",
+        |head| riff_form(head) == Some(b"WEBP"),
+        "image/webp",
+    ),
+    (
+        b"RIFF",
+        |head| riff_form(head) == Some(b"WAVE"),
+        "audio/wav",
+    ),
+    (b"", |head| ftyp_brand(head) == Some(b"avif"), "image/avif"),
+    (b"", |head| ftyp_brand(head) == Some(b"heic"), "image/heic"),
+    (b"", |head| ftyp_brand(head).is_some(), "video/mp4"),
+];
+
+/// Short signatures that text could start with too, only tried once the
+/// file turned out not to be text.
+const BINARY_ONLY: &[(&[u8], &str)] = &[
+    (b"BZh", "application/x-bzip2"),
+    (b"ID3", "audio/mpeg"),
+    (b"fLaC", "audio/flac"),
+    (b"OggS", "audio/ogg"),
+];
+
+/// Archive and compression formats, for [`Kind::Archive`](crate::matcher::Kind).
+pub(crate) const ARCHIVES: &[&str] = &[
+    "application/zip",
+    "application/gzip",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/zstd",
+    "application/x-7z-compressed",
+    "application/vnd.rar",
+    "application/x-tar",
+];
+
+/// Script types by interpreter, as named on a `#!` line.
+const INTERPRETERS: &[(&[&str], &str)] = &[
+    (
+        &["sh", "bash", "dash", "zsh", "ksh", "ash", "fish"],
+        "text/x-shellscript",
+    ),
+    (&["python", "pypy"], "text/x-python"),
+    (&["perl"], "text/x-perl"),
+    (&["ruby"], "text/x-ruby"),
+    (&["node", "nodejs", "deno", "bun"], "text/javascript"),
+    (&["php"], "text/x-php"),
+    (&["lua", "luajit"], "text/x-lua"),
+    (&["awk", "gawk", "mawk"], "text/x-awk"),
+    (&["tclsh", "wish"], "text/x-tcl"),
+];
+
+/// Scripts with an interpreter not listed above.
+const OTHER_SCRIPT: &str = "text/x-script";
+
+/// The MIME type of the file at `path`, from its first bytes.
+pub(crate) fn sniff(path: &Path) -> io::Result<&'static str> {
+    let mut head = Vec::new();
+    File::open(path)?.take(SNIFF_LEN).read_to_end(&mut head)?;
+
+    Ok(detect(&head))
+}
+
+fn detect(head: &[u8]) -> &'static str {
+    if head.is_empty() {
+        return "inode/x-empty";
+    }
+
+    let signature = SIGNATURES
+        .iter()
+        .find(|(offset, magic, _)| head.get(*offset..).is_some_and(|h| h.starts_with(magic)));
+    if let Some(&(_, _, mime)) = signature {
+        return mime;
+    }
+
+    let checked = CHECKED
+        .iter()
+        .find(|(magic, check, _)| head.starts_with(magic) && check(head));
+    if let Some(&(_, _, mime)) = checked {
+        return mime;
+    }
+
+    if let Some(line) = head.strip_prefix(b"#!") {
+        return interpreter(line);
+    }
+
+    if is_text(head) {
+        let start = String::from_utf8_lossy(&head[..head.len().min(256)]);
+        if start.contains("<svg") {
+            return "image/svg+xml";
+        }
+        return "text/plain";
+    }
+
+    BINARY_ONLY
+        .iter()
+        .find(|(magic, _)| head.starts_with(magic))
+        .map_or("application/octet-stream", |&(_, mime)| mime)
+}
+
+/// A directory of at least one icon, whose first entry has its reserved
+/// byte clear.
+fn is_ico(head: &[u8]) -> bool {
+    u16_le(head, 4).is_some_and(|count| count > 0) && head.get(9) == Some(&0)
+}
+
+/// The info header that follows the file header starts with its own size,
+/// which is one of a few known values.
+fn is_bmp(head: &[u8]) -> bool {
+    u32_le(head, 14).is_some_and(|size| [12, 40, 52, 56, 64, 108, 124].contains(&size))
+}
+
+/// The DOS header points to the `PE\0\0` signature at `e_lfanew`.
+fn is_pe(head: &[u8]) -> bool {
+    u32_le(head, 0x3c)
+        .and_then(|offset| head.get(usize::try_from(offset).ok()?..))
+        .is_some_and(|pe| pe.starts_with(b"PE\0\0"))
+}
+
+/// The form type of a RIFF container, e.g. `WEBP` or `WAVE`. Its first
+/// twelve bytes may well be text, so the rest must not be.
+fn riff_form(head: &[u8]) -> Option<&[u8]> {
+    head.get(8..12).filter(|_| !is_text(head))
+}
+
+/// The major brand of an ISO media file, which starts with an `ftyp` box.
+/// Real boxes are small, so the size's high byte is 0 and text can't pass.
+fn ftyp_brand(head: &[u8]) -> Option<&[u8]> {
+    let size = u32::from_be_bytes(head.get(..4)?.try_into().ok()?);
+
+    ((8..=1024).contains(&size) && head.get(4..8) == Some(b"ftyp")).then(|| head.get(8..12))?
+}
+
+fn u16_le(head: &[u8], offset: usize) -> Option<u16> {
+    let bytes = head.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn u32_le(head: &[u8], offset: usize) -> Option<u32> {
+    let bytes = head.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+/// Whether `mime` is one [`sniff`] gives scripts.
+pub(crate) fn is_script(mime: &str) -> bool {
+    mime == OTHER_SCRIPT || INTERPRETERS.iter().any(|&(_, script)| script == mime)
+}
+
+/// Script type from the rest of a `#!` line, e.g. `/usr/bin/env python3`.
+fn interpreter(line: &[u8]) -> &'static str {
+    let line = line.split(|&b| b == b'\n').next().unwrap_or_default();
+    let line = String::from_utf8_lossy(line);
+    let mut words = line.split_whitespace();
+
+    let mut program = words.next().unwrap_or_default();
+    program = program.rsplit('/').next().unwrap_or(program);
+
+    if program == "env" {
+        // `env -S python3 -u` and the like
+        program = words.find(|w| !w.starts_with('-')).unwrap_or_default();
+    }
+
+    // `python3.12` is still python
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    INTERPRETERS
+        .iter()
+        .find(|(names, _)| names.contains(&name))
+        .map_or(OTHER_SCRIPT, |&(_, mime)| mime)
+}
+
+/// UTF-8 (or UTF-16 with a byte order mark) without NUL bytes. The sample
+/// may end in the middle of a character.
+fn is_text(head: &[u8]) -> bool {
+    if head.starts_with(b"\xff\xfe") || head.starts_with(b"\xfe\xff") {
+        return true;
+    }
+    if head.contains(&0) {
+        return false;
+    }
+
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_starting_like_a_short_signature() {
+        assert_eq!(detect(b"BMW notes\n"), "text/plain");
+        assert_eq!(detect(b"MZ is a postcode\n"), "text/plain");
+        assert_eq!(
+            detect(b"BZh, ID3 and fLaC are not archives\n"),
+            "text/plain"
+        );
+        assert_eq!(detect(b"OggS"), "text/plain");
+        assert_eq!(detect(b"The ftyp box is first\n"), "text/plain");
+        assert_eq!(detect(b"abcdftypavif\n"), "text/plain");
+        assert_eq!(detect(b"abcdefghWEBP is a format\n"), "text/plain");
+        assert_eq!(detect(b"RIFF or WAVE, I forget\n"), "text/plain");
+    }
+
+    #[test]
+    fn checked_signatures() {
+        let mut bmp = b"BM".to_vec();
+        bmp.resize(14, 0);
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+        assert_eq!(detect(&bmp), "image/bmp");
+
+        let mut pe = b"MZ".to_vec();
+        pe.resize(0x3c, 0);
+        pe.extend_from_slice(&0x80u32.to_le_bytes());
+        pe.resize(0x80, 0);
+        pe.extend_from_slice(b"PE\0\0");
+        assert_eq!(detect(&pe), "application/vnd.microsoft.portable-executable");
+
+        // A DOS stub without a PE header is just binary
+        pe.truncate(0x40);
+        assert_eq!(detect(&pe), "application/octet-stream");
+
+        assert_eq!(detect(b"RIFF\x24\0\0\0WAVEfmt "), "audio/wav");
+        assert_eq!(detect(b"\0\0\0\x20ftypisom\0\0\x02\0"), "video/mp4");
+        assert_eq!(detect(b"\0\0\0\x1cftypavif\0\0\0\0"), "image/avif");
+        assert_eq!(detect(b"\0\0\0\x18ftypheic\0\0\0\0"), "image/heic");
+
+        assert_eq!(detect(b"\0\0\x01\0\x01\0\x10\x10\0\0"), "image/x-icon");
+        assert_eq!(detect(b"\0\0\x01\0\0\0"), "application/octet-stream");
+    }
+
+    #[test]
+    fn binary_only_signatures() {
+        assert_eq!(detect(b"BZh91AY&SY\xff\x00"), "application/x-bzip2");
+        assert_eq!(detect(b"ID3\x03\0\0\0"), "audio/mpeg");
+    }
+
+    #[test]
+    fn signatures() {
+        assert_eq!(detect(b"\x89PNG\r\n\x1a\n...."), "image/png");
+        assert_eq!(detect(b"%PDF-1.7\n"), "application/pdf");
+        assert_eq!(detect(b"\x7fELF\x02\x01"), "application/x-elf");
+        assert_eq!(detect(b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+
+        let mut tar = vec![0; 257];
+        tar.extend_from_slice(b"ustar\x0000");
+        assert_eq!(detect(&tar), "application/x-tar");
+    }
+
+    #[test]
+    fn text_and_scripts() {
+        assert_eq!(detect(b""), "inode/x-empty");
+        assert_eq!(detect("h\u{e9}llo\n".as_bytes()), "text/plain");
+        assert_eq!(detect(b"<?xml?><svg xmlns=\"...\">"), "image/svg+xml");
+        assert_eq!(detect(b"#!/bin/sh\necho"), "text/x-shellscript");
+        assert_eq!(
+            detect(b"#!/usr/bin/env -S python3.12 -u\n"),
+            "text/x-python"
+        );
+        assert_eq!(detect(b"#!/opt/bin/frob\n"), "text/x-script");
+        assert_eq!(detect(b"caf\xc3"), "text/plain");
+        assert_eq!(detect(b"a\0b"), "application/octet-stream");
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::process;
+
+mod cli;
+mod color;
+mod long;
+mod output;
+mod template;
+mod tree;
+
+fn main() {
+    let (args, matches) = cli::parse();
+
+    let expr = args.expression(&matches).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    });
+
+    if args.explain {
+        match &expr {
+            Some(expr) => print!("{expr}"),
+            None => println!("(no tests, every entry matches)"),
+        }
+        return;
+    }
+
+    let mut query = args.query();
+
+    if let Some(expr) = expr {
+        query = query.filter(expr.into_matcher());
+    }
+
+    // Block-buffer unless someone is watching the results come in
+    let stdout = io::stdout().lock();
+    let terminal = stdout.is_terminal();
+    let out: Box<dyn Write> = if terminal {
+        Box::new(stdout)
+    } else {
+        Box::new(BufWriter::new(stdout))
+    };
+    let mut printer = args.printer(out, terminal).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    });
+
+    // After the printer, so a bad --format fails before any walking
+    let search = query.search().unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(1);
+    });
+
+    let mut failed = false;
+
+    for result in search {
+        let printed = match result {
+            Ok(m) => printer.print(&m),
+            Err(e) => {
+                eprintln!("{e}");
+                failed = true;
+                Ok(())
+            }
+        };
+        printed.unwrap_or_else(|e| write_failed(e));
+    }
+
+    printer.finish().unwrap_or_else(|e| write_failed(e));
+
+    // Like find, unreadable entries don't stop the walk but do fail it
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// A closed pipe (`lookfor | head`) just means nobody wants more output.
+fn write_failed(e: io::Error) -> ! {
+    if e.kind() == io::ErrorKind::BrokenPipe {
+        process::exit(0);
+    }
+    eprintln!("Could not write output: {e}");
+    process::exit(1);
+}
@@ -0,0 +1,47 @@
+use clap::ValueEnum;
+use unicase::UniCase;
+
+/// How name and extension patterns treat letter case.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Case {
+    /// Match case exactly
+    Sensitive,
+    /// Ignore case, with full Unicode case folding
+    Insensitive,
+    /// Ignore case unless the pattern contains an uppercase letter
+    #[default]
+    Smart,
+}
+
+impl Case {
+    /// Whether `pattern` should be matched ignoring case. For regular
+    /// expressions, pass `escapes` so `\W`, `\S` and friends don't count as
+    /// uppercase letters.
+    pub fn ignores(self, pattern: &str, escapes: bool) -> bool {
+        match self {
+            Case::Sensitive => false,
+            Case::Insensitive => true,
+            Case::Smart => !has_uppercase(pattern, escapes),
+        }
+    }
+}
+
+fn has_uppercase(pattern: &str, escapes: bool) -> bool {
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        if escapes && c == '\\' {
+            chars.next();
+        } else if c.is_uppercase() {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Full Unicode case folding, e.g. `Straße` and `STRASSE` both fold to
+/// `strasse`.
+pub(crate) fn fold(s: &str) -> String {
+    UniCase::new(s).to_folded_case()
+}
@@ -0,0 +1,153 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use regex::bytes::Regex;
+
+use crate::entry::Entry;
+use crate::error::Error;
+use crate::matcher::Matcher;
+use crate::matcher::case::Case;
+
+/// How much of the start of a file is checked for NUL bytes.
+const BINARY_SNIFF: usize = 8 * 1024;
+
+/// Matches regular files with a line containing a substring or matching a
+/// regular expression, or any of several.
+///
+/// Files are read line by line and need not be UTF-8. Files with a NUL byte
+/// near the start are taken to be binary and never match. Letter case
+/// follows the same rules as [`Name`](crate::matcher::Name) regexes.
+#[derive(Clone, Debug)]
+pub struct Contains {
+    regex: Regex,
+    max_filesize: Option<u64>,
+}
+
+impl Contains {
+    pub fn substring(pattern: &str, case: Case) -> Self {
+        Contains::substrings([pattern], case)
+    }
+
+    pub fn substrings<S: AsRef<str>>(patterns: impl IntoIterator<Item = S>, case: Case) -> Self {
+        let escaped: Vec<String> = patterns
+            .into_iter()
+            .map(|p| regex::escape(p.as_ref()))
+            .collect();
+
+        Contains::regexes(escaped, case).expect("escaped patterns are valid regexes")
+    }
+
+    pub fn regex(pattern: &str, case: Case) -> Result<Self, Error> {
+        Contains::regexes([pattern], case)
+    }
+
+    pub fn regexes<S: AsRef<str>>(
+        patterns: impl IntoIterator<Item = S>,
+        case: Case,
+    ) -> Result<Self, Error> {
+        let mut alternatives = Vec::new();
+
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+
+            // Compiled on its own first, so syntax errors name the pattern
+            if let Err(source) = Regex::new(pattern) {
+                return Err(Error::Regex {
+                    pattern: pattern.to_string(),
+                    source,
+                });
+            }
+
+            alternatives.push(if case.ignores(pattern, true) {
+                format!("(?i:{pattern})")
+            } else {
+                format!("(?:{pattern})")
+            });
+        }
+
+        let pattern = alternatives.join("|");
+        let regex = Regex::new(&pattern).map_err(|source| Error::Regex { pattern, source })?;
+
+        Ok(Contains {
+            regex,
+            max_filesize: None,
+        })
+    }
+
+    /// Don't read files larger than `bytes`; they never match.
+    pub fn max_filesize(mut self, bytes: u64) -> Self {
+        self.max_filesize = Some(bytes);
+        self
+    }
+
+    /// The matching lines of the file at `path`, numbered from 1 and
+    /// without their line ending. Empty for binary files.
+    pub fn matching_lines(&self, path: &Path) -> io::Result<Vec<(usize, Vec<u8>)>> {
+        let mut lines = Vec::new();
+
+        self.scan(path, |n, line| {
+            lines.push((n, line.to_vec()));
+            true
+        })?;
+
+        Ok(lines)
+    }
+
+    /// Calls `found` with each matching line until it returns `false`.
+    fn scan(&self, path: &Path, mut found: impl FnMut(usize, &[u8]) -> bool) -> io::Result<()> {
+        let mut reader = BufReader::with_capacity(64 * 1024, File::open(path)?);
+
+        if reader
+            .fill_buf()?
+            .iter()
+            .take(BINARY_SNIFF)
+            .any(|&b| b == 0)
+        {
+            return Ok(());
+        }
+
+        let mut line = Vec::new();
+        let mut n = 0;
+
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                return Ok(());
+            }
+            n += 1;
+
+            let text = line.strip_suffix(b"\n").unwrap_or(&line);
+            let text = text.strip_suffix(b"\r").unwrap_or(text);
+
+            if self.regex.is_match(text) && !found(n, text) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Matcher for Contains {
+    fn is_match(&self, entry: &Entry) -> bool {
+        if !entry.file_type().is_file() {
+            return false;
+        }
+        if let Some(max) = self.max_filesize
+            && entry.metadata().is_none_or(|m| m.len() > max)
+        {
+            return false;
+        }
+
+        let mut matched = false;
+        let _ = self.scan(entry.path(), |_, _| {
+            matched = true;
+            false
+        });
+
+        matched
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
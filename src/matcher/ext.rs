@@ -0,0 +1,50 @@
+use crate::entry::Entry;
+use crate::matcher::Matcher;
+use crate::matcher::case::{Case, fold};
+
+/// Matches on file extension (e.g. `rs`, `txt`), or any of several.
+#[derive(Clone, Debug)]
+pub struct Extension {
+    /// Each with whether it ignores case; those that do are stored folded.
+    exts: Vec<(String, bool)>,
+}
+
+impl Extension {
+    pub fn new(ext: impl Into<String>, case: Case) -> Self {
+        Extension::any_of([ext], case)
+    }
+
+    pub fn any_of<S: Into<String>>(exts: impl IntoIterator<Item = S>, case: Case) -> Self {
+        let exts = exts
+            .into_iter()
+            .map(|ext| {
+                let ext = ext.into();
+
+                if case.ignores(&ext, false) {
+                    (fold(&ext), true)
+                } else {
+                    (ext, false)
+                }
+            })
+            .collect();
+
+        Extension { exts }
+    }
+}
+
+impl Matcher for Extension {
+    fn is_match(&self, entry: &Entry) -> bool {
+        let Some(ext) = entry.path().extension().and_then(|e| e.to_str()) else {
+            return false;
+        };
+        let mut folded = None;
+
+        self.exts.iter().any(|(wanted, ignore_case)| {
+            if *ignore_case {
+                *folded.get_or_insert_with(|| fold(ext)) == *wanted
+            } else {
+                ext == wanted
+            }
+        })
+    }
+}
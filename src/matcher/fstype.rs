@@ -0,0 +1,33 @@
+use crate::entry::Entry;
+use crate::matcher::Matcher;
+use crate::mounts;
+
+/// Matches entries on a filesystem of the given type (`ext4`, `tmpfs`,
+/// `nfs`, ...), or any of several. Types come from the mount table, which is
+/// only read on Linux; elsewhere nothing matches.
+#[derive(Clone, Debug)]
+pub struct FsType {
+    types: Vec<String>,
+}
+
+impl FsType {
+    pub fn new(fs_type: impl Into<String>) -> Self {
+        FsType::any_of([fs_type])
+    }
+
+    pub fn any_of<S: Into<String>>(types: impl IntoIterator<Item = S>) -> Self {
+        FsType {
+            types: types.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Matcher for FsType {
+    fn is_match(&self, entry: &Entry) -> bool {
+        entry
+            .metadata()
+            .and_then(mounts::device)
+            .and_then(mounts::fs_type)
+            .is_some_and(|fs_type| mounts::is_any_of(fs_type, &self.types))
+    }
+}
@@ -0,0 +1,94 @@
+use clap::ValueEnum;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::entry::Entry;
+use crate::error::Error;
+use crate::magic;
+use crate::matcher::Matcher;
+
+/// Matches on the MIME type detected from an entry's contents (see
+/// [`Entry::mime`]), by exact type or glob like `image/*`, ignoring case.
+#[derive(Clone, Debug)]
+pub struct Mime {
+    types: GlobSet,
+}
+
+impl Mime {
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        Mime::any_of([pattern])
+    }
+
+    pub fn any_of<S: AsRef<str>>(patterns: impl IntoIterator<Item = S>) -> Result<Self, Error> {
+        let mut set = GlobSetBuilder::new();
+        let mut all = Vec::new();
+
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            let glob = GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|source| Error::Glob {
+                    pattern: pattern.to_string(),
+                    source,
+                })?;
+
+            set.add(glob);
+            all.push(pattern.to_string());
+        }
+
+        set.build()
+            .map(|types| Mime { types })
+            .map_err(|source| Error::Glob {
+                pattern: all.join(", "),
+                source,
+            })
+    }
+}
+
+impl Matcher for Mime {
+    fn is_match(&self, entry: &Entry) -> bool {
+        entry.mime().is_some_and(|mime| self.types.is_match(mime))
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
+
+/// Broad file categories, judged by contents like [`Mime`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Kind {
+    /// Any `image/*` type.
+    Image,
+    /// Archives and compressed files: zip, tar, gzip, bzip2, xz, zstd, 7z, rar.
+    Archive,
+    /// Text files, scripts included.
+    Text,
+    /// ELF executables, libraries and object files.
+    Elf,
+    /// Files starting with a `#!` line.
+    Script,
+}
+
+impl Matcher for Kind {
+    fn is_match(&self, entry: &Entry) -> bool {
+        if !entry.file_type().is_file() {
+            return false;
+        }
+        let Some(mime) = entry.mime() else {
+            return false;
+        };
+
+        match self {
+            Kind::Image => mime.starts_with("image/"),
+            Kind::Archive => magic::ARCHIVES.contains(&mime),
+            Kind::Text => mime.starts_with("text/"),
+            Kind::Elf => mime == "application/x-elf",
+            Kind::Script => magic::is_script(mime),
+        }
+    }
+
+    fn is_expensive(&self) -> bool {
+        true
+    }
+}
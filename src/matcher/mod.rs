@@ -0,0 +1,257 @@
+//! Predicates deciding which entries a search reports.
+//!
+//! Every filter is a [`Matcher`]; they compose with [`And`], [`Or`] and
+//! [`Not`]. Any `Fn(&Entry) -> bool` closure is a matcher too:
+//!
+//! ```no_run
+//! use lookfor::Query;
+//! use lookfor::matcher::{Case, Extension, Not, Or};
+//!
+//! let docs = Or::new()
+//!     .with(Extension::new("md", Case::Smart))
+//!     .with(Extension::new("txt", Case::Smart));
+//! let query = Query::new(".")
+//!     .filter(docs)
+//!     .filter(Not(|e: &lookfor::Entry| e.depth() > 3));
+//! ```
+
+mod case;
+mod contains;
+mod ext;
+mod fstype;
+mod mime;
+mod name;
+#[cfg(unix)]
+mod owner;
+#[cfg(unix)]
+mod perm;
+mod size;
+mod time;
+
+use std::fmt;
+use std::fs::{self, FileType, Metadata};
+use std::sync::Arc;
+
+use crate::entry::Entry;
+use crate::query::FileTypeFilter;
+
+pub use case::Case;
+pub use contains::Contains;
+pub use ext::Extension;
+pub use fstype::FsType;
+pub use mime::{Kind, Mime};
+pub use name::Name;
+#[cfg(unix)]
+pub use owner::Owner;
+#[cfg(unix)]
+pub use perm::{Access, Perm};
+pub use size::{Bytes, Empty, Size};
+pub use time::{Time, TimeField, Timestamp};
+
+/// Decides whether an entry belongs in the results.
+pub trait Matcher: Send + Sync {
+    fn is_match(&self, entry: &Entry) -> bool;
+
+    /// Whether deciding takes more than the entry's name and metadata, e.g.
+    /// reading the file. [`And`] and [`Or`] check such matchers last.
+    fn is_expensive(&self) -> bool {
+        false
+    }
+}
+
+impl<F> Matcher for F
+where
+    F: Fn(&Entry) -> bool + Send + Sync,
+{
+    fn is_match(&self, entry: &Entry) -> bool {
+        self(entry)
+    }
+}
+
+impl Matcher for Box<dyn Matcher> {
+    fn is_match(&self, entry: &Entry) -> bool {
+        (**self).is_match(entry)
+    }
+
+    fn is_expensive(&self) -> bool {
+        (**self).is_expensive()
+    }
+}
+
+impl Matcher for Arc<dyn Matcher> {
+    fn is_match(&self, entry: &Entry) -> bool {
+        (**self).is_match(entry)
+    }
+
+    fn is_expensive(&self) -> bool {
+        (**self).is_expensive()
+    }
+}
+
+/// Adds `matcher` after the cheap matchers but before the expensive ones,
+/// keeping each group in the order given.
+fn insert(matchers: &mut Vec<Arc<dyn Matcher>>, matcher: impl Matcher + 'static) {
+    let at = if matcher.is_expensive() {
+        matchers.len()
+    } else {
+        matchers
+            .iter()
+            .position(|m| m.is_expensive())
+            .unwrap_or(matchers.len())
+    };
+
+    matchers.insert(at, Arc::new(matcher));
+}
+
+/// Matches when every inner matcher does (and when there are none).
+///
+/// Evaluation stops at the first failing matcher, so put cheap ones first;
+/// [expensive](Matcher::is_expensive) ones are moved last automatically.
+#[derive(Clone, Default)]
+pub struct And(Vec<Arc<dyn Matcher>>);
+
+impl And {
+    pub fn new() -> Self {
+        And::default()
+    }
+
+    pub fn with(mut self, matcher: impl Matcher + 'static) -> Self {
+        self.push(matcher);
+        self
+    }
+
+    pub fn push(&mut self, matcher: impl Matcher + 'static) {
+        insert(&mut self.0, matcher);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Matcher for And {
+    fn is_match(&self, entry: &Entry) -> bool {
+        self.0.iter().all(|m| m.is_match(entry))
+    }
+
+    fn is_expensive(&self) -> bool {
+        self.0.iter().any(|m| m.is_expensive())
+    }
+}
+
+impl fmt::Debug for And {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "And({} matchers)", self.0.len())
+    }
+}
+
+/// Matches when any inner matcher does (never, when there are none).
+///
+/// Evaluation stops at the first successful matcher;
+/// [expensive](Matcher::is_expensive) ones are tried last.
+#[derive(Clone, Default)]
+pub struct Or(Vec<Arc<dyn Matcher>>);
+
+impl Or {
+    pub fn new() -> Self {
+        Or::default()
+    }
+
+    pub fn with(mut self, matcher: impl Matcher + 'static) -> Self {
+        self.push(matcher);
+        self
+    }
+
+    pub fn push(&mut self, matcher: impl Matcher + 'static) {
+        insert(&mut self.0, matcher);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Matcher for Or {
+    fn is_match(&self, entry: &Entry) -> bool {
+        self.0.iter().any(|m| m.is_match(entry))
+    }
+
+    fn is_expensive(&self) -> bool {
+        self.0.iter().any(|m| m.is_expensive())
+    }
+}
+
+impl fmt::Debug for Or {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Or({} matchers)", self.0.len())
+    }
+}
+
+/// Inverts the inner matcher.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Not<M>(pub M);
+
+impl<M: Matcher> Matcher for Not<M> {
+    fn is_match(&self, entry: &Entry) -> bool {
+        !self.0.is_match(entry)
+    }
+
+    fn is_expensive(&self) -> bool {
+        self.0.is_expensive()
+    }
+}
+
+impl Matcher for FileTypeFilter {
+    fn is_match(&self, entry: &Entry) -> bool {
+        let file_type = entry.file_type();
+
+        match self {
+            FileTypeFilter::File => file_type.is_file(),
+            FileTypeFilter::Dir => file_type.is_dir(),
+            FileTypeFilter::Symlink => file_type.is_symlink(),
+            FileTypeFilter::BrokenSymlink => {
+                file_type.is_symlink() && fs::metadata(entry.path()).is_err()
+            }
+            FileTypeFilter::Executable => {
+                file_type.is_file() && entry.metadata().is_some_and(is_executable)
+            }
+            FileTypeFilter::Socket
+            | FileTypeFilter::Fifo
+            | FileTypeFilter::BlockDevice
+            | FileTypeFilter::CharDevice => is_special(*self, file_type),
+            FileTypeFilter::Any => true,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+/// Other platforms have no execute bits to go by.
+#[cfg(not(unix))]
+fn is_executable(_: &Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn is_special(filter: FileTypeFilter, file_type: FileType) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    match filter {
+        FileTypeFilter::Socket => file_type.is_socket(),
+        FileTypeFilter::Fifo => file_type.is_fifo(),
+        FileTypeFilter::BlockDevice => file_type.is_block_device(),
+        FileTypeFilter::CharDevice => file_type.is_char_device(),
+        _ => false,
+    }
+}
+
+/// Sockets, fifos and device files are a unix notion.
+#[cfg(not(unix))]
+fn is_special(_: FileTypeFilter, _: FileType) -> bool {
+    false
+}
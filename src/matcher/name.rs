@@ -0,0 +1,189 @@
+use std::ffi::OsStr;
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::bytes::{Regex, RegexSet, RegexSetBuilder};
+
+use crate::entry::Entry;
+use crate::error::Error;
+use crate::matcher::Matcher;
+use crate::matcher::case::{Case, fold};
+
+/// Matches on the file/directory name by substring, regular expression or
+/// glob. With [`Name::full_path`] the path relative to the search root is
+/// matched instead of the name alone.
+///
+/// Several patterns can be given at once; the name matches if any of them
+/// does. Regexes and globs are compiled into a single set, so checking many
+/// patterns costs about as much as checking one.
+///
+/// Substrings ignoring case use full Unicode case folding; regexes and globs
+/// use the simple (one character to one character) folding of the `regex`
+/// crate, so only substrings match `strasse` to `Straße`. With
+/// [`Case::Smart`] every pattern decides for itself.
+#[derive(Clone, Debug)]
+pub struct Name {
+    pattern: Pattern,
+    full_path: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Pattern {
+    /// Each with whether it ignores case; those that do are stored folded.
+    Substrings(Vec<(String, bool)>),
+    Regexes(RegexSet),
+    Globs(GlobSet),
+}
+
+impl Name {
+    pub fn substring(pattern: impl Into<String>, case: Case) -> Self {
+        Name::substrings([pattern], case)
+    }
+
+    pub fn substrings<S: Into<String>>(patterns: impl IntoIterator<Item = S>, case: Case) -> Self {
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| {
+                let pattern = pattern.into();
+
+                if case.ignores(&pattern, false) {
+                    (fold(&pattern), true)
+                } else {
+                    (pattern, false)
+                }
+            })
+            .collect();
+
+        Name::from(Pattern::Substrings(patterns))
+    }
+
+    pub fn regex(pattern: &str, case: Case) -> Result<Self, Error> {
+        Name::regexes([pattern], case)
+    }
+
+    pub fn regexes<S: AsRef<str>>(
+        patterns: impl IntoIterator<Item = S>,
+        case: Case,
+    ) -> Result<Self, Error> {
+        let patterns: Vec<String> = patterns
+            .into_iter()
+            .map(|p| p.as_ref().to_string())
+            .collect();
+
+        // Case is decided per pattern, so it goes into each pattern's flags
+        let flagged = patterns.iter().map(|pattern| {
+            if case.ignores(pattern, true) {
+                format!("(?i:{pattern})")
+            } else {
+                pattern.clone()
+            }
+        });
+
+        RegexSetBuilder::new(flagged)
+            .build()
+            .map(|set| Name::from(Pattern::Regexes(set)))
+            .map_err(|set_error| {
+                // Pin syntax errors on the pattern causing them
+                for pattern in &patterns {
+                    if let Err(source) = Regex::new(pattern) {
+                        return Error::Regex {
+                            pattern: pattern.clone(),
+                            source,
+                        };
+                    }
+                }
+
+                Error::Regex {
+                    pattern: patterns.join(", "),
+                    source: set_error,
+                }
+            })
+    }
+
+    /// Shell-style glob: `*`, `?`, `[a-z]`, `{a,b}` and `**` across
+    /// directories. `*` never matches a `/`.
+    pub fn glob(pattern: &str, case: Case) -> Result<Self, Error> {
+        Name::globs([pattern], case)
+    }
+
+    pub fn globs<S: AsRef<str>>(
+        patterns: impl IntoIterator<Item = S>,
+        case: Case,
+    ) -> Result<Self, Error> {
+        let mut set = GlobSetBuilder::new();
+        let mut all = Vec::new();
+
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .case_insensitive(case.ignores(pattern, false))
+                .build()
+                .map_err(|source| Error::Glob {
+                    pattern: pattern.to_string(),
+                    source,
+                })?;
+
+            set.add(glob);
+            all.push(pattern.to_string());
+        }
+
+        set.build()
+            .map(|set| Name::from(Pattern::Globs(set)))
+            .map_err(|source| Error::Glob {
+                pattern: all.join(", "),
+                source,
+            })
+    }
+
+    /// Match against the path relative to the search root (e.g.
+    /// `src/lib.rs`) rather than the name alone.
+    pub fn full_path(mut self, yes: bool) -> Self {
+        self.full_path = yes;
+        self
+    }
+}
+
+impl From<Pattern> for Name {
+    fn from(pattern: Pattern) -> Self {
+        Name {
+            pattern,
+            full_path: false,
+        }
+    }
+}
+
+impl Matcher for Name {
+    fn is_match(&self, entry: &Entry) -> bool {
+        // Raw bytes, so names that aren't valid UTF-8 can still match
+        let subject: &OsStr = if self.full_path {
+            entry.relative_path().as_os_str()
+        } else {
+            entry.file_name()
+        };
+        let bytes = subject.as_encoded_bytes();
+
+        match &self.pattern {
+            Pattern::Substrings(patterns) => {
+                let mut folded = None;
+
+                patterns.iter().any(|(pattern, ignore_case)| {
+                    if *ignore_case {
+                        // Invalid bytes become U+FFFD, which patterns don't contain
+                        folded
+                            .get_or_insert_with(|| fold(&subject.to_string_lossy()))
+                            .contains(pattern.as_str())
+                    } else {
+                        contains(bytes, pattern.as_bytes())
+                    }
+                })
+            }
+            Pattern::Regexes(set) => set.is_match(bytes),
+            Pattern::Globs(set) => set.is_match(Path::new(subject)),
+        }
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    needle.is_empty() || haystack.windows(needle.len()).any(|w| w == needle)
+}
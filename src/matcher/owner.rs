@@ -0,0 +1,58 @@
+use std::os::unix::fs::MetadataExt;
+
+use crate::entry::Entry;
+use crate::error::Error;
+use crate::matcher::Matcher;
+use crate::users;
+
+/// Matches on the owning user or group of an entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Owner {
+    User(u32),
+    Group(u32),
+    /// Owned by a user id that has no entry in the user database.
+    NoUser,
+    /// Owned by a group id that has no entry in the group database.
+    NoGroup,
+}
+
+impl Owner {
+    /// Owned by the user with this name or, failing that, numeric id.
+    pub fn user(spec: &str) -> Result<Self, Error> {
+        users::user_id(spec)
+            .or_else(|| spec.parse().ok())
+            .map(Owner::User)
+            .ok_or_else(|| unknown("user", spec))
+    }
+
+    /// Owned by the group with this name or, failing that, numeric id.
+    pub fn group(spec: &str) -> Result<Self, Error> {
+        users::group_id(spec)
+            .or_else(|| spec.parse().ok())
+            .map(Owner::Group)
+            .ok_or_else(|| unknown("group", spec))
+    }
+}
+
+fn unknown(kind: &'static str, spec: &str) -> Error {
+    Error::Value {
+        kind,
+        value: spec.to_string(),
+        reason: format!("no such {kind}"),
+    }
+}
+
+impl Matcher for Owner {
+    fn is_match(&self, entry: &Entry) -> bool {
+        let Some(metadata) = entry.metadata() else {
+            return false;
+        };
+
+        match *self {
+            Owner::User(uid) => metadata.uid() == uid,
+            Owner::Group(gid) => metadata.gid() == gid,
+            Owner::NoUser => users::user_name(metadata.uid()).is_none(),
+            Owner::NoGroup => users::group_name(metadata.gid()).is_none(),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::str::FromStr;
+
+use crate::entry::Entry;
+use crate::error::Error;
+use crate::matcher::Matcher;
+
+/// Matches on permission bits, like find's `-perm`.
+///
+/// Parsed from an octal (`644`, `4755`) or symbolic (`u=rw,go=r`, `a+x`,
+/// `u+s`) mode, optionally prefixed with `-` (all of these bits are set) or
+/// `/` (any of these bits is set). Without a prefix the mode must match
+/// exactly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Perm {
+    mode: u32,
+    how: PermMatch,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PermMatch {
+    Exact,
+    All,
+    Any,
+}
+
+impl Matcher for Perm {
+    fn is_match(&self, entry: &Entry) -> bool {
+        let Some(mode) = entry.metadata().map(|m| m.permissions().mode() & 0o7777) else {
+            return false;
+        };
+
+        match self.how {
+            PermMatch::Exact => mode == self.mode,
+            PermMatch::All => mode & self.mode == self.mode,
+            // find treats `/000` as matching everything
+            PermMatch::Any => self.mode == 0 || mode & self.mode != 0,
+        }
+    }
+}
+
+impl FromStr for Perm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let (how, mode) = if let Some(mode) = s.strip_prefix('-') {
+            (PermMatch::All, mode)
+        } else if let Some(mode) = s.strip_prefix('/') {
+            (PermMatch::Any, mode)
+        } else {
+            (PermMatch::Exact, s)
+        };
+
+        let mode = if mode.chars().all(|c| c.is_digit(8)) && !mode.is_empty() {
+            u32::from_str_radix(mode, 8)
+                .ok()
+                .filter(|&m| m <= 0o7777)
+                .ok_or("octal mode above 7777")
+        } else {
+            parse_symbolic(mode)
+        };
+
+        mode.map(|mode| Perm { mode, how })
+            .map_err(|reason| Error::Value {
+                kind: "mode",
+                value: s.to_string(),
+                reason: reason.to_string(),
+            })
+    }
+}
+
+/// Parses comma-separated `[ugoa]*[+=][rwxst]*` clauses, starting from no
+/// bits set.
+fn parse_symbolic(s: &str) -> Result<u32, &'static str> {
+    const USAGE: &str = "expected an octal mode like 644 or symbolic clauses like u=rw,go+r";
+    let mut mode = 0;
+
+    for clause in s.split(',') {
+        let op = clause.find(['+', '=']).ok_or(USAGE)?;
+        let (who, perms) = clause.split_at(op);
+
+        let mut who_mask = 0;
+        for c in who.chars() {
+            who_mask |= match c {
+                'u' => 0o4700,
+                'g' => 0o2070,
+                'o' => 0o1007,
+                'a' => 0o7777,
+                _ => return Err(USAGE),
+            };
+        }
+        if who_mask == 0 {
+            who_mask = 0o7777;
+        }
+
+        let mut bits = 0;
+        for c in perms[1..].chars() {
+            bits |= match c {
+                'r' => 0o444,
+                'w' => 0o222,
+                'x' => 0o111,
+                's' => 0o6000,
+                't' => 0o1000,
+                _ => return Err(USAGE),
+            };
+        }
+
+        if perms.starts_with('=') {
+            // `=` replaces the permissions of those classes
+            mode &= !(who_mask & 0o777);
+        }
+        mode |= who_mask & bits;
+    }
+
+    Ok(mode)
+}
+
+/// Matches entries the current user may read, write or execute, judged by
+/// the effective user and group ids like `test -r/-w/-x`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Access {
+    Readable,
+    Writable,
+    Executable,
+}
+
+impl Matcher for Access {
+    fn is_match(&self, entry: &Entry) -> bool {
+        let mode = match self {
+            Access::Readable => libc::R_OK,
+            Access::Writable => libc::W_OK,
+            Access::Executable => libc::X_OK,
+        };
+        let Ok(path) = CString::new(entry.path().as_os_str().as_bytes()) else {
+            return false;
+        };
+
+        // SAFETY: `path` is a valid NUL-terminated string for the duration of the call
+        unsafe { libc::faccessat(libc::AT_FDCWD, path.as_ptr(), mode, libc::AT_EACCESS) == 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn perm(s: &str) -> (u32, PermMatch) {
+        let perm: Perm = s.parse().unwrap();
+        (perm.mode, perm.how)
+    }
+
+    #[test]
+    fn octal() {
+        assert_eq!(perm("644"), (0o644, PermMatch::Exact));
+        assert_eq!(perm("-4000"), (0o4000, PermMatch::All));
+        assert_eq!(perm("/022"), (0o022, PermMatch::Any));
+        assert!("10000".parse::<Perm>().is_err());
+    }
+
+    #[test]
+    fn symbolic() {
+        assert_eq!(perm("u=rw,go=r"), (0o644, PermMatch::Exact));
+        assert_eq!(perm("a+x"), (0o111, PermMatch::Exact));
+        assert_eq!(perm("+w"), (0o222, PermMatch::Exact));
+        assert_eq!(perm("-u+s"), (0o4000, PermMatch::All));
+        assert_eq!(perm("/g+s,o+t"), (0o3000, PermMatch::Any));
+        assert_eq!(perm("u=rwx,u=r"), (0o400, PermMatch::Exact));
+    }
+
+    #[test]
+    fn invalid() {
+        for bad in ["", "-", "u", "z+r", "u+q", "689"] {
+            assert!(bad.parse::<Perm>().is_err(), "{bad:?} parsed");
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs;
+use std::str::FromStr;
+
+use crate::entry::Entry;
+use crate::error::Error;
+use crate::matcher::Matcher;
+
+/// Matches regular files whose size lies in a range.
+///
+/// Parsed from `+N` (more than N), `-N` (less than N), `N` (exactly N) or
+/// an inclusive range `N..M` where either end may be left out. `N` takes
+/// an optional unit: `b`, `k`/`m`/`g`/`t` (powers of 1024, like find),
+/// `kib`/`mib`/... (the same) or `kb`/`mb`/... (powers of 1000). Units
+/// ignore case.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Size {
+    /// Inclusive bounds, in bytes.
+    min: u64,
+    max: u64,
+}
+
+impl Size {
+    /// Between `min` and `max` bytes, inclusive.
+    pub fn between(min: u64, max: u64) -> Self {
+        Size { min, max }
+    }
+}
+
+impl Matcher for Size {
+    fn is_match(&self, entry: &Entry) -> bool {
+        entry.file_type().is_file()
+            && entry
+                .metadata()
+                .is_some_and(|m| (self.min..=self.max).contains(&m.len()))
+    }
+}
+
+impl FromStr for Size {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = |reason: &str| Error::Value {
+            kind: "size",
+            value: s.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let size = if let Some(more) = s.strip_prefix('+') {
+            let n = parse_bytes(more).map_err(invalid)?;
+            Size::between(
+                n.checked_add(1).ok_or_else(|| invalid("too large"))?,
+                u64::MAX,
+            )
+        } else if let Some(less) = s.strip_prefix('-') {
+            let n = parse_bytes(less).map_err(invalid)?;
+            Size::between(
+                0,
+                n.checked_sub(1)
+                    .ok_or_else(|| invalid("nothing is smaller than 0"))?,
+            )
+        } else if let Some((min, max)) = s.split_once("..") {
+            let min = match min {
+                "" => 0,
+                min => parse_bytes(min).map_err(invalid)?,
+            };
+            let max = match max {
+                "" => u64::MAX,
+                max => parse_bytes(max).map_err(invalid)?,
+            };
+            if min > max {
+                return Err(invalid("range is empty"));
+            }
+            Size::between(min, max)
+        } else {
+            let n = parse_bytes(s).map_err(invalid)?;
+            Size::between(n, n)
+        };
+
+        Ok(size)
+    }
+}
+
+/// A number of bytes, with the same optional units as [`Size`] (`512k`,
+/// `10M`, `1GB`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bytes(pub u64);
+
+impl FromStr for Bytes {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        parse_bytes(s).map(Bytes).map_err(|reason| Error::Value {
+            kind: "size",
+            value: s.to_string(),
+            reason: reason.to_string(),
+        })
+    }
+}
+
+/// Parses a number with an optional unit suffix into bytes.
+fn parse_bytes(s: &str) -> Result<u64, &'static str> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+
+    if number.is_empty() {
+        return Err("expected a number, optionally followed by a unit like k, M or GB");
+    }
+
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        _ => return Err("unknown unit (use b, k, M, G, T, or KB, MB, ... for powers of 1000)"),
+    };
+
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or("too large")
+}
+
+/// Matches empty regular files and directories without entries.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Empty;
+
+impl Matcher for Empty {
+    fn is_match(&self, entry: &Entry) -> bool {
+        let file_type = entry.file_type();
+
+        if file_type.is_file() {
+            entry.metadata().is_some_and(|m| m.len() == 0)
+        } else if file_type.is_dir() {
+            fs::read_dir(entry.path()).is_ok_and(|mut entries| entries.next().is_none())
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn size(s: &str) -> Size {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn bounds() {
+        assert_eq!(size("+100"), Size::between(101, u64::MAX));
+        assert_eq!(size("-4k"), Size::between(0, 4095));
+        assert_eq!(size("512"), Size::between(512, 512));
+        assert_eq!(size("1M..2M"), Size::between(1 << 20, 2 << 20));
+        assert_eq!(size("..10"), Size::between(0, 10));
+        assert_eq!(size("10.."), Size::between(10, u64::MAX));
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(size("1b"), size("1"));
+        assert_eq!(size("2K"), size("2kib"));
+        assert_eq!(size("3G"), Size::between(3 << 30, 3 << 30));
+        assert_eq!(size("1kb"), Size::between(1_000, 1_000));
+        assert_eq!(
+            size("1TB"),
+            Size::between(1_000_000_000_000, 1_000_000_000_000)
+        );
+        assert_eq!("10M".parse::<Bytes>().unwrap(), Bytes(10 << 20));
+    }
+
+    #[test]
+    fn invalid() {
+        for bad in [
+            "",
+            "+",
+            "k",
+            "10x",
+            "-0",
+            "2..1",
+            "1.5M",
+            "99999999999999999999",
+        ] {
+            assert!(bad.parse::<Size>().is_err(), "{bad:?} parsed");
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use std::fs::{self, Metadata};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+
+use crate::entry::Entry;
+use crate::error::Error;
+use crate::matcher::Matcher;
+
+/// Which of an entry's timestamps a [`Time`] test looks at.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimeField {
+    /// Last modification of the contents (mtime).
+    Modified,
+    /// Last access (atime).
+    Accessed,
+    /// Last status change, e.g. permissions or owner (ctime). Unix only.
+    Changed,
+    /// Creation (birth time). Not every platform and filesystem records it.
+    Created,
+}
+
+impl TimeField {
+    /// The timestamp, if the platform provides it.
+    pub fn of(self, metadata: &Metadata) -> Option<SystemTime> {
+        match self {
+            TimeField::Modified => metadata.modified().ok(),
+            TimeField::Accessed => metadata.accessed().ok(),
+            TimeField::Changed => status_changed(metadata),
+            TimeField::Created => metadata.created().ok(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn status_changed(metadata: &Metadata) -> Option<SystemTime> {
+    use std::os::unix::fs::MetadataExt;
+
+    // Pre-1970 status changes don't happen in practice
+    let secs = u64::try_from(metadata.ctime()).ok()?;
+    let nanos = u32::try_from(metadata.ctime_nsec()).ok()?;
+
+    UNIX_EPOCH.checked_add(Duration::new(secs, nanos))
+}
+
+#[cfg(not(unix))]
+fn status_changed(_: &Metadata) -> Option<SystemTime> {
+    None
+}
+
+/// Matches entries whose timestamp lies on one side of a point in time.
+/// Entries without that timestamp never match.
+#[derive(Copy, Clone, Debug)]
+pub struct Time {
+    field: TimeField,
+    bound: Bound,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Bound {
+    AtOrAfter(SystemTime),
+    After(SystemTime),
+    Before(SystemTime),
+}
+
+impl Time {
+    /// At or after `since`, e.g. "changed within the last 2 hours".
+    pub fn since(field: TimeField, since: SystemTime) -> Self {
+        Time {
+            field,
+            bound: Bound::AtOrAfter(since),
+        }
+    }
+
+    /// Strictly before `until`.
+    pub fn before(field: TimeField, until: SystemTime) -> Self {
+        Time {
+            field,
+            bound: Bound::Before(until),
+        }
+    }
+
+    /// Strictly newer than the modification time of `reference`, like
+    /// find's `-newer`, `-anewer` and `-cnewer`.
+    pub fn newer_than(field: TimeField, reference: &Path) -> Result<Self, Error> {
+        let modified = fs::metadata(reference)
+            .and_then(|m| m.modified())
+            .map_err(|e| Error::Value {
+                kind: "reference file",
+                value: reference.display().to_string(),
+                reason: e.to_string(),
+            })?;
+
+        Ok(Time {
+            field,
+            bound: Bound::After(modified),
+        })
+    }
+}
+
+impl Matcher for Time {
+    fn is_match(&self, entry: &Entry) -> bool {
+        let Some(time) = entry.metadata().and_then(|m| self.field.of(m)) else {
+            return false;
+        };
+
+        match self.bound {
+            Bound::AtOrAfter(t) => time >= t,
+            Bound::After(t) => time > t,
+            Bound::Before(t) => time < t,
+        }
+    }
+}
+
+/// A point in time, given either as a duration back from now (`90s`, `2h`,
+/// `3d`, `1w`, `1h30m`) or as an ISO-8601 date or date-time
+/// (`2024-05-01`, `2024-05-01T13:30`, `2024-05-01 13:30:00+02:00`). Times
+/// without an offset are local.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Timestamp(pub SystemTime);
+
+impl FromStr for Timestamp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = |reason: &str| Error::Value {
+            kind: "time",
+            value: s.to_string(),
+            reason: reason.to_string(),
+        };
+
+        if s.starts_with(|c: char| c.is_ascii_digit()) && !s.contains('-') {
+            let ago = parse_duration(s).map_err(invalid)?;
+            return SystemTime::now()
+                .checked_sub(ago)
+                .map(Timestamp)
+                .ok_or_else(|| invalid("too far in the past"));
+        }
+
+        parse_date(s).map(|t| Timestamp(t.into())).ok_or_else(|| {
+            invalid("expected a duration like 2h or 3d, or a date like 2024-05-01[T13:30[:00]]")
+        })
+    }
+}
+
+/// Parses `1h30m`-style durations: numbers each followed by a unit.
+fn parse_duration(s: &str) -> Result<Duration, &'static str> {
+    let mut total = Duration::ZERO;
+    let mut rest = s;
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(digits);
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+
+        let number: u64 = number.parse().map_err(|_| "expected a number")?;
+        let seconds: u64 = match unit {
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+            "d" | "day" | "days" => 24 * 60 * 60,
+            "w" | "week" | "weeks" => 7 * 24 * 60 * 60,
+            "y" | "year" | "years" => 365 * 24 * 60 * 60,
+            "" => return Err("missing unit (s, m, h, d, w or y)"),
+            _ => return Err("unknown unit (use s, m, h, d, w or y)"),
+        };
+
+        total += number
+            .checked_mul(seconds)
+            .map(Duration::from_secs)
+            .ok_or("too large")?;
+        rest = tail;
+    }
+
+    Ok(total)
+}
+
+fn parse_date(s: &str) -> Option<DateTime<Local>> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.with_timezone(&Local));
+    }
+
+    const FORMATS: [&str; 4] = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ];
+
+    let naive = FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })?;
+
+    Local.from_local_datetime(&naive).earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2days"), Ok(Duration::from_secs(2 * 86400)));
+        assert_eq!(parse_duration("1w"), Ok(Duration::from_secs(7 * 86400)));
+        assert!(parse_duration("10").is_err());
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("99999999999999999999y").is_err());
+    }
+
+    #[test]
+    fn dates() {
+        let local = |y, mo, d, h, mi, s| {
+            Local
+                .with_ymd_and_hms(y, mo, d, h, mi, s)
+                .earliest()
+                .unwrap()
+        };
+
+        assert_eq!(parse_date("2024-05-01"), Some(local(2024, 5, 1, 0, 0, 0)));
+        assert_eq!(
+            parse_date("2024-05-01T13:30"),
+            Some(local(2024, 5, 1, 13, 30, 0))
+        );
+        assert_eq!(
+            parse_date("2024-05-01 13:30:15"),
+            Some(local(2024, 5, 1, 13, 30, 15))
+        );
+        assert_eq!(
+            parse_date("2024-05-01T13:30:00+02:00").map(|t| t.timestamp()),
+            Some(1714563000)
+        );
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn timestamps() {
+        let Timestamp(ago) = "1h".parse().unwrap();
+        let age = SystemTime::now().duration_since(ago).unwrap();
+        assert!(age >= Duration::from_secs(3600) && age < Duration::from_secs(3660));
+
+        assert!("2024-05-01".parse::<Timestamp>().is_ok());
+        assert!("1h-ago".parse::<Timestamp>().is_err());
+        assert!("soon".parse::<Timestamp>().is_err());
+    }
+}
@@ -0,0 +1,70 @@
+//! Which filesystem an entry lives on, for `--one-file-system` and the
+//! filesystem type filters.
+
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::sync::LazyLock;
+
+/// Filesystem type by device id, read once from the mount table.
+static FS_TYPES: LazyLock<HashMap<u64, String>> = LazyLock::new(load);
+
+/// Device id of the filesystem holding an entry. `None` where the platform
+/// doesn't expose one.
+pub(crate) fn device(metadata: &Metadata) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        Some(metadata.dev())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+/// Type of the filesystem with device id `dev`, as the mount table names it
+/// (`ext4`, `tmpfs`, `fuse.sshfs`, ...).
+pub(crate) fn fs_type(dev: u64) -> Option<&'static str> {
+    FS_TYPES.get(&dev).map(String::as_str)
+}
+
+/// Whether `fs_type` is one of `types`. A type also covers its numbered
+/// versions and subtypes, so `nfs` matches `nfs4` and `fuse` matches
+/// `fuse.sshfs`.
+pub(crate) fn is_any_of(fs_type: &str, types: &[String]) -> bool {
+    types.iter().any(|t| {
+        fs_type
+            .strip_prefix(t.as_str())
+            .is_some_and(|rest| rest.starts_with('.') || rest.bytes().all(|b| b.is_ascii_digit()))
+    })
+}
+
+/// Parses `/proc/self/mountinfo`, whose lines look like
+/// `36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw`.
+#[cfg(target_os = "linux")]
+fn load() -> HashMap<u64, String> {
+    let Ok(table) = std::fs::read_to_string("/proc/self/mountinfo") else {
+        return HashMap::new();
+    };
+
+    table
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (major, minor) = fields.nth(2)?.split_once(':')?;
+            let dev = libc::makedev(major.parse().ok()?, minor.parse().ok()?);
+            let fs_type = fields.skip_while(|&f| f != "-").nth(1)?;
+
+            Some((dev, fs_type.to_string()))
+        })
+        .collect()
+}
+
+/// Only Linux's mount table is read so far; elsewhere no entry has a known
+/// filesystem type.
+#[cfg(not(target_os = "linux"))]
+fn load() -> HashMap<u64, String> {
+    HashMap::new()
+}
@@ -0,0 +1,366 @@
+//! How matches are written to stdout.
+//!
+//! The structured formats share one record schema, documented in the README
+//! ("structured output"). Keep the two in sync; pipelines depend on it.
+
+use std::fs::{self, FileType};
+use std::io::{self, Write};
+use std::time::SystemTime;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use lookfor::Match;
+use lookfor::matcher::{Contains, TimeField};
+use serde_json::Value;
+
+use crate::color::Colors;
+use crate::long::Listing;
+use crate::template::Template;
+use crate::tree::Tree;
+
+/// Output formats for `--output`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// One path per line
+    #[default]
+    Plain,
+    /// A JSON array of records
+    Json,
+    /// One JSON record per line
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+/// Record fields for the structured formats, see the module docs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "snake_case")]
+pub enum Field {
+    Path,
+    RelativePath,
+    Name,
+    Ext,
+    Type,
+    Depth,
+    Size,
+    Mtime,
+    Atime,
+    Ctime,
+    Created,
+    Mode,
+    Uid,
+    Gid,
+    User,
+    Group,
+    Inode,
+    Nlink,
+    Mime,
+    Target,
+}
+
+impl Field {
+    pub const DEFAULT: [Field; 7] = [
+        Field::Path,
+        Field::Name,
+        Field::Type,
+        Field::Depth,
+        Field::Size,
+        Field::Mtime,
+        Field::Mode,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Field::Path => "path",
+            Field::RelativePath => "relative_path",
+            Field::Name => "name",
+            Field::Ext => "ext",
+            Field::Type => "type",
+            Field::Depth => "depth",
+            Field::Size => "size",
+            Field::Mtime => "mtime",
+            Field::Atime => "atime",
+            Field::Ctime => "ctime",
+            Field::Created => "created",
+            Field::Mode => "mode",
+            Field::Uid => "uid",
+            Field::Gid => "gid",
+            Field::User => "user",
+            Field::Group => "group",
+            Field::Inode => "inode",
+            Field::Nlink => "nlink",
+            Field::Mime => "mime",
+            Field::Target => "target",
+        }
+    }
+
+    pub fn value(self, m: &Match) -> Value {
+        let metadata = m.metadata();
+
+        match self {
+            Field::Path => m.path().to_string_lossy().into(),
+            Field::RelativePath => m.entry().relative_path().to_string_lossy().into(),
+            Field::Name => m.file_name().to_string_lossy().into(),
+            Field::Ext => m.path().extension().map(|ext| ext.to_string_lossy()).into(),
+            Field::Type => type_name(m.file_type()).into(),
+            Field::Depth => m.depth().into(),
+            Field::Size => metadata.map(|md| md.len()).into(),
+            Field::Mtime => time(m, TimeField::Modified),
+            Field::Atime => time(m, TimeField::Accessed),
+            Field::Ctime => time(m, TimeField::Changed),
+            Field::Created => time(m, TimeField::Created),
+            Field::Mime => m.mime().into(),
+            Field::Target => fs::read_link(m.path())
+                .ok()
+                .map(|target| target.to_string_lossy().into_owned())
+                .into(),
+            #[cfg(unix)]
+            Field::Mode
+            | Field::Uid
+            | Field::Gid
+            | Field::User
+            | Field::Group
+            | Field::Inode
+            | Field::Nlink => {
+                use std::os::unix::fs::MetadataExt;
+
+                let Some(md) = metadata else {
+                    return Value::Null;
+                };
+                match self {
+                    Field::Mode => format!("{:04o}", md.mode() & 0o7777).into(),
+                    Field::Uid => md.uid().into(),
+                    Field::Gid => md.gid().into(),
+                    Field::User => lookfor::users::user_name(md.uid()).into(),
+                    Field::Group => lookfor::users::group_name(md.gid()).into(),
+                    Field::Inode => md.ino().into(),
+                    _ => md.nlink().into(),
+                }
+            }
+            #[cfg(not(unix))]
+            _ => Value::Null,
+        }
+    }
+}
+
+fn time(m: &Match, field: TimeField) -> Value {
+    m.metadata()
+        .and_then(|md| field.of(md))
+        .map(|t: SystemTime| DateTime::<Utc>::from(t).to_rfc3339_opts(SecondsFormat::Secs, true))
+        .into()
+}
+
+fn type_name(file_type: FileType) -> &'static str {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+
+        if file_type.is_socket() {
+            return "socket";
+        } else if file_type.is_fifo() {
+            return "fifo";
+        } else if file_type.is_block_device() {
+            return "block-device";
+        } else if file_type.is_char_device() {
+            return "char-device";
+        }
+    }
+
+    if file_type.is_file() {
+        "file"
+    } else if file_type.is_dir() {
+        "dir"
+    } else if file_type.is_symlink() {
+        "symlink"
+    } else {
+        "other"
+    }
+}
+
+/// Writes matches in one of the [`Format`]s.
+pub struct Printer<W: Write> {
+    out: W,
+    format: Format,
+    fields: Vec<Field>,
+    /// Replaces the bare path in plain output.
+    template: Option<Template>,
+    /// Colors for plain paths, if enabled.
+    colors: Option<Colors>,
+    /// Ends each plain path: a newline, or NUL with `--print0`.
+    terminator: u8,
+    /// Content search whose matching lines go below each path.
+    show_matches: Option<Contains>,
+    /// Rows of a long listing, written by [`Printer::finish`].
+    long: Option<Listing>,
+    /// Matches for a tree view, written by [`Printer::finish`].
+    tree: Option<Tree>,
+    /// Records written so far.
+    count: usize,
+}
+
+impl<W: Write> Printer<W> {
+    pub fn new(out: W, format: Format, fields: Vec<Field>) -> Self {
+        Printer {
+            out,
+            format,
+            fields,
+            template: None,
+            colors: None,
+            terminator: b'\n',
+            show_matches: None,
+            long: None,
+            tree: None,
+            count: 0,
+        }
+    }
+
+    /// Print each match as `template` renders it instead of its path.
+    pub fn template(mut self, template: Template) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    /// Color plain paths (templates stay as they are).
+    pub fn colors(mut self, colors: Colors) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    /// End plain paths with NUL instead of a newline, for `xargs -0`.
+    pub fn print0(mut self, yes: bool) -> Self {
+        self.terminator = if yes { b'\0' } else { b'\n' };
+        self
+    }
+
+    /// Print the lines `contains` matches below each file (plain output).
+    pub fn show_matches(mut self, contains: Contains) -> Self {
+        self.show_matches = Some(contains);
+        self
+    }
+
+    /// List permissions, links, owner, group, size and mtime before each
+    /// path, in columns (plain output).
+    pub fn long(mut self, yes: bool) -> Self {
+        self.long = yes.then(Listing::default);
+        self
+    }
+
+    /// Draw the matches under their ancestor directories (plain output).
+    pub fn tree(mut self, yes: bool) -> Self {
+        self.tree = yes.then(Tree::default);
+        self
+    }
+
+    pub fn print(&mut self, m: &Match) -> io::Result<()> {
+        match self.format {
+            Format::Plain if self.tree.is_some() => {
+                if let Some(tree) = &mut self.tree {
+                    tree.push(m, self.colors.as_ref());
+                }
+            }
+            Format::Plain if self.long.is_some() => {
+                let mut path = Vec::new();
+                self.path(m, &mut path);
+                if let Some(listing) = &mut self.long {
+                    listing.push(m, path);
+                }
+            }
+            Format::Plain => self.plain(m)?,
+            Format::Json => {
+                let separator = if self.count == 0 { "[\n" } else { ",\n" };
+                write!(self.out, "{separator}{}", self.json(m))?;
+            }
+            Format::Ndjson => writeln!(self.out, "{}", self.json(m))?,
+            Format::Csv => {
+                if self.count == 0 {
+                    let header: Vec<&str> = self.fields.iter().map(|f| f.name()).collect();
+                    writeln!(self.out, "{}", header.join(","))?;
+                }
+                let row: Vec<String> = self.fields.iter().map(|f| csv_cell(&f.value(m))).collect();
+                writeln!(self.out, "{}", row.join(","))?;
+            }
+        }
+
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Writes what had to wait for all matches, closes the JSON array (empty
+    /// if nothing matched) and flushes.
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(listing) = &self.long {
+            listing.write(&mut self.out)?;
+        }
+        if let Some(tree) = &self.tree {
+            tree.write(&mut self.out)?;
+        }
+        if self.format == Format::Json {
+            let end = if self.count == 0 { "[]\n" } else { "\n]\n" };
+            self.out.write_all(end.as_bytes())?;
+        }
+        self.out.flush()
+    }
+
+    /// Paths and matching lines go out as raw bytes, so nothing is lost to
+    /// UTF-8 replacement.
+    fn plain(&mut self, m: &Match) -> io::Result<()> {
+        match &self.template {
+            Some(template) => {
+                let mut line = Vec::new();
+                template.render(m, &mut line);
+                self.out.write_all(&line)?;
+            }
+            None => {
+                let mut line = Vec::new();
+                self.path(m, &mut line);
+                self.out.write_all(&line)?;
+            }
+        }
+        self.out.write_all(&[self.terminator])?;
+
+        if let Some(contains) = &self.show_matches
+            && m.file_type().is_file()
+        {
+            for (n, line) in contains.matching_lines(m.path()).unwrap_or_default() {
+                write!(self.out, "    {n}: ")?;
+                self.out.write_all(&line)?;
+                self.out.write_all(b"\n")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The path of `m`, colored if enabled.
+    fn path(&self, m: &Match, out: &mut Vec<u8>) {
+        match &self.colors {
+            Some(colors) => colors.paint(m, out),
+            None => out.extend_from_slice(m.path().as_os_str().as_encoded_bytes()),
+        }
+    }
+
+    fn json(&self, m: &Match) -> String {
+        let members: Vec<String> = self
+            .fields
+            .iter()
+            .map(|f| format!("{}:{}", Value::from(f.name()), f.value(m)))
+            .collect();
+
+        format!("{{{}}}", members.join(","))
+    }
+}
+
+/// RFC 4180 quoting, only where needed.
+fn csv_cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => return String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
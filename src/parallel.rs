@@ -0,0 +1,226 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+use walkdir::{DirEntry, WalkDir};
+
+use crate::entry::Entry;
+use crate::error::Error;
+use crate::follow::{self, Ancestors};
+use crate::ignores::Ignore;
+use crate::prune::Prune;
+use crate::query::{Filter, Match};
+
+/// How many results workers may get ahead of the consumer.
+const BACKLOG: usize = 1024;
+
+/// Everything the workers need to walk and filter on their own.
+pub(crate) struct Walk {
+    pub(crate) root: PathBuf,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) follow: bool,
+    pub(crate) follow_depth: Option<usize>,
+    pub(crate) prune: Prune,
+    pub(crate) filter: Filter,
+}
+
+/// A directory waiting to be read.
+struct Dir {
+    path: PathBuf,
+    depth: usize,
+    rules: Option<Arc<Ignore>>,
+    /// Directories above this one, when following links.
+    ancestors: Option<Arc<Ancestors>>,
+    /// Symlinks followed to get here.
+    links: usize,
+}
+
+struct Shared {
+    walk: Walk,
+    queue: Mutex<Queue>,
+    ready: Condvar,
+}
+
+struct Queue {
+    dirs: Vec<Dir>,
+    /// Workers currently reading a directory, and so possibly queueing more.
+    busy: usize,
+    /// Set once the consumer hung up; everyone stops.
+    quit: bool,
+}
+
+/// Walks with `threads` workers, each reading whole directories and applying
+/// the prune rules and matchers itself. Results arrive in no particular
+/// order; the channel closes when the walk is done.
+pub(crate) fn spawn(walk: Walk, threads: usize) -> Receiver<Result<Match, Error>> {
+    let (tx, rx) = mpsc::sync_channel(BACKLOG);
+
+    let root = match WalkDir::new(&walk.root).max_depth(0).into_iter().next() {
+        Some(Ok(root)) => root,
+        Some(Err(e)) => {
+            let _ = tx.send(Err(e.into()));
+            return rx;
+        }
+        None => return rx,
+    };
+
+    if !walk.prune.keep(&root, 0, None) {
+        return rx;
+    }
+
+    let mut dirs = Vec::new();
+
+    if root.file_type().is_dir() && walk.max_depth != Some(0) {
+        dirs.push(Dir {
+            path: root.path().to_path_buf(),
+            depth: 0,
+            rules: walk.prune.rules_for(&root, None),
+            ancestors: None,
+            links: 0,
+        });
+    }
+
+    if let Some(m) = walk.filter.check(Entry::new(root, 0)) {
+        let _ = tx.send(Ok(m));
+    }
+
+    let shared = Arc::new(Shared {
+        walk,
+        queue: Mutex::new(Queue {
+            dirs,
+            busy: 0,
+            quit: false,
+        }),
+        ready: Condvar::new(),
+    });
+
+    for _ in 0..threads.max(1) {
+        let shared = shared.clone();
+        let tx = tx.clone();
+
+        thread::spawn(move || {
+            while let Some(dir) = shared.next_dir() {
+                let delivered = shared.read_dir(&dir, &tx);
+                shared.finish_dir(!delivered);
+            }
+        });
+    }
+
+    rx
+}
+
+impl Shared {
+    /// Blocks until there is a directory to read, or returns `None` once the
+    /// queue is empty and no worker can add to it anymore.
+    fn next_dir(&self) -> Option<Dir> {
+        let mut queue = self.queue.lock().unwrap();
+
+        loop {
+            if queue.quit {
+                return None;
+            }
+            if let Some(dir) = queue.dirs.pop() {
+                queue.busy += 1;
+                return Some(dir);
+            }
+            if queue.busy == 0 {
+                return None;
+            }
+            queue = self.ready.wait(queue).unwrap();
+        }
+    }
+
+    fn push_dir(&self, dir: Dir) {
+        self.queue.lock().unwrap().dirs.push(dir);
+        self.ready.notify_one();
+    }
+
+    fn finish_dir(&self, quit: bool) {
+        let mut queue = self.queue.lock().unwrap();
+
+        queue.busy -= 1;
+        queue.quit |= quit;
+
+        if queue.quit || (queue.busy == 0 && queue.dirs.is_empty()) {
+            self.ready.notify_all();
+        }
+    }
+
+    /// Handles every entry of `dir`. Returns `false` if the consumer is gone.
+    fn read_dir(&self, dir: &Dir, tx: &SyncSender<Result<Match, Error>>) -> bool {
+        let walk = &self.walk;
+        let depth = dir.depth + 1;
+
+        let ancestors = if walk.follow {
+            Ancestors::push(dir.ancestors.clone(), &dir.path).ok()
+        } else {
+            None
+        };
+
+        let entries = WalkDir::new(&dir.path)
+            .min_depth(1)
+            .max_depth(1)
+            .follow_links(walk.follow);
+
+        for entry in entries {
+            let entry: DirEntry = match entry {
+                Ok(entry) => entry,
+                Err(e) => match follow::broken_link(&e) {
+                    Some(link) => link,
+                    None => {
+                        if tx.send(Err(e.into())).is_err() {
+                            return false;
+                        }
+                        continue;
+                    }
+                },
+            };
+
+            if !walk.prune.keep(&entry, depth, dir.rules.as_deref()) {
+                continue;
+            }
+
+            if entry.file_type().is_dir() {
+                // Only links can lead back up, so only they are checked
+                let ancestor = match &ancestors {
+                    Some(ancestors) if entry.path_is_symlink() => ancestors.find(entry.path()),
+                    _ => None,
+                };
+                if let Some(ancestor) = ancestor {
+                    let error = Error::Loop {
+                        path: entry.into_path(),
+                        ancestor: ancestor.to_path_buf(),
+                    };
+                    if tx.send(Err(error)).is_err() {
+                        return false;
+                    }
+                    continue;
+                }
+
+                let links = dir.links + usize::from(entry.path_is_symlink());
+
+                if walk.max_depth.is_none_or(|max| depth < max)
+                    && walk.follow_depth.is_none_or(|max| links <= max)
+                    && walk.prune.descend(&entry, depth)
+                {
+                    self.push_dir(Dir {
+                        path: entry.path().to_path_buf(),
+                        depth,
+                        rules: walk.prune.rules_for(&entry, dir.rules.as_ref()),
+                        ancestors: ancestors.clone(),
+                        links,
+                    });
+                }
+            }
+
+            if let Some(m) = walk.filter.check(Entry::new(entry, depth))
+                && tx.send(Ok(m)).is_err()
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+}
@@ -0,0 +1,274 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use walkdir::DirEntry;
+
+use crate::error::Error;
+use crate::ignores::Ignore;
+use crate::mounts;
+
+/// Decides which entries the walk never yields nor descends into.
+///
+/// Everything that should cut a whole subtree goes through here, so hidden
+/// directories, `--exclude-dir` matches and ignored paths are skipped before
+/// any of their children are read.
+#[derive(Clone, Debug)]
+pub(crate) struct Prune {
+    root: PathBuf,
+    absolute_root: PathBuf,
+    hidden: bool,
+    /// `--exclude-dir` globs without a `/`, matched against the name.
+    exclude_names: GlobSet,
+    /// `--exclude-dir` globs with a `/`, matched against the relative path.
+    exclude_paths: GlobSet,
+    ignore: bool,
+    ignore_vcs: bool,
+    /// Device of the root, when the walk must stay on its filesystem.
+    root_device: Option<u64>,
+    exclude_fstypes: Vec<String>,
+    /// The root itself is on one of `exclude_fstypes`.
+    root_excluded: bool,
+}
+
+impl Prune {
+    /// Patterns containing a `/` match the path relative to `root`, all
+    /// others match the directory name alone.
+    pub(crate) fn new(
+        root: &Path,
+        hidden: bool,
+        exclude_dirs: &[String],
+        ignore: bool,
+        ignore_vcs: bool,
+    ) -> Result<Self, Error> {
+        let (paths, names): (Vec<&String>, Vec<&String>) =
+            exclude_dirs.iter().partition(|p| p.contains('/'));
+
+        let exclude_names = glob_set(&names)?;
+        let exclude_paths = glob_set(&paths)?;
+
+        Ok(Prune {
+            root: root.to_path_buf(),
+            absolute_root: std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf()),
+            hidden,
+            exclude_names,
+            exclude_paths,
+            ignore,
+            ignore_vcs,
+            root_device: None,
+            exclude_fstypes: Vec::new(),
+            root_excluded: false,
+        })
+    }
+
+    /// Don't descend into directories on another filesystem than the root.
+    pub(crate) fn one_file_system(mut self, yes: bool) -> Self {
+        self.root_device = if yes {
+            fs::metadata(&self.root)
+                .ok()
+                .and_then(|m| mounts::device(&m))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Skip directories on filesystems of these types, mount points included.
+    /// A root on one of them yields nothing at all.
+    pub(crate) fn exclude_fstypes(mut self, types: &[String]) -> Self {
+        self.exclude_fstypes = types.to_vec();
+        self.root_excluded = fs::metadata(&self.root)
+            .ok()
+            .is_some_and(|m| self.is_excluded_fstype(&m));
+        self
+    }
+
+    /// Whether the walk should keep `entry`, found `depth` levels below the
+    /// root, given the ignore rules of the directory it is in. The root itself
+    /// is kept unless it is on an excluded filesystem.
+    pub(crate) fn keep(&self, entry: &DirEntry, depth: usize, rules: Option<&Ignore>) -> bool {
+        if depth == 0 {
+            return !self.root_excluded;
+        }
+
+        if !self.hidden && is_hidden(entry) {
+            return false;
+        }
+
+        let is_dir = entry.file_type().is_dir();
+
+        let excluded_fstype = || entry.metadata().is_ok_and(|m| self.is_excluded_fstype(&m));
+
+        if is_dir && (self.is_excluded_dir(entry) || excluded_fstype()) {
+            return false;
+        }
+
+        !rules.is_some_and(|rules| rules.is_ignored(&self.absolute(entry.path()), is_dir))
+    }
+
+    /// Whether the walk should enter `dir`, a directory it keeps. Mount points
+    /// of other filesystems are reported but not entered with
+    /// [`Prune::one_file_system`].
+    pub(crate) fn descend(&self, dir: &DirEntry, depth: usize) -> bool {
+        let Some(root) = self.root_device else {
+            return true;
+        };
+
+        depth == 0 || dir.metadata().ok().and_then(|m| mounts::device(&m)) == Some(root)
+    }
+
+    /// Ignore rules inside `dir`, which the walk is about to descend into.
+    /// `parent` holds the rules of the directory containing it, if any.
+    pub(crate) fn rules_for(
+        &self,
+        dir: &DirEntry,
+        parent: Option<&Arc<Ignore>>,
+    ) -> Option<Arc<Ignore>> {
+        if !self.ignore {
+            return None;
+        }
+
+        let path = self.absolute(dir.path());
+
+        Some(match parent {
+            Some(parent) => parent.child(&path),
+            None => Ignore::root(&path, self.ignore_vcs),
+        })
+    }
+
+    /// Ignore files anchor their patterns at absolute directories, so walked
+    /// paths are translated before matching.
+    fn absolute(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.root) {
+            Ok(relative) if relative.as_os_str().is_empty() => self.absolute_root.clone(),
+            Ok(relative) => self.absolute_root.join(relative),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+
+    fn is_excluded_dir(&self, entry: &DirEntry) -> bool {
+        if self.exclude_names.is_match(entry.file_name()) {
+            return true;
+        }
+        if self.exclude_paths.is_empty() {
+            return false;
+        }
+
+        let relative = entry
+            .path()
+            .strip_prefix(&self.root)
+            .unwrap_or(entry.path());
+
+        self.exclude_paths.is_match(relative)
+    }
+
+    fn is_excluded_fstype(&self, metadata: &fs::Metadata) -> bool {
+        if self.exclude_fstypes.is_empty() {
+            return false;
+        }
+
+        mounts::device(metadata)
+            .and_then(mounts::fs_type)
+            .is_some_and(|fs_type| mounts::is_any_of(fs_type, &self.exclude_fstypes))
+    }
+}
+
+pub(crate) fn is_hidden(entry: &DirEntry) -> bool {
+    entry
+        .file_name()
+        .to_str()
+        .map(|s| s.starts_with('.'))
+        .unwrap_or(false)
+}
+
+/// `*` and `?` stop at `/`, so `a*b` can't reach into subdirectories.
+fn glob_set(patterns: &[&String]) -> Result<GlobSet, Error> {
+    let mut globs = GlobSetBuilder::new();
+
+    for &pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|source| Error::Glob {
+                pattern: pattern.clone(),
+                source,
+            })?;
+        globs.add(glob);
+    }
+
+    globs.build().map_err(|source| Error::Glob {
+        pattern: patterns
+            .iter()
+            .map(|p| p.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use walkdir::WalkDir;
+
+    /// Directories under a fresh tree that survive `--exclude-dir patterns`,
+    /// relative to its root.
+    fn kept(test: &str, patterns: &[&str]) -> Vec<String> {
+        let root =
+            std::env::temp_dir().join(format!("lookfor-prune-{}-{test}", std::process::id()));
+        for dir in ["src/a", "lib/src/a", "a/x/b", "ab", "deep/er/target"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        let prune = Prune::new(&root, true, &patterns, false, false).unwrap();
+
+        let mut kept = Vec::new();
+        let mut walk = WalkDir::new(&root).min_depth(1).into_iter();
+        while let Some(entry) = walk.next() {
+            let entry = entry.unwrap();
+            if prune.keep(&entry, entry.depth(), None) {
+                let relative = entry.path().strip_prefix(&root).unwrap();
+                kept.push(relative.to_string_lossy().replace('\\', "/"));
+            } else {
+                walk.skip_current_dir();
+            }
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+        kept.sort();
+        kept
+    }
+
+    #[test]
+    fn path_patterns_match_from_the_root() {
+        let dirs = kept("path", &["src/a"]);
+
+        assert!(!dirs.contains(&"src/a".to_string()));
+        assert!(dirs.contains(&"src".to_string()));
+        assert!(dirs.contains(&"lib/src/a".to_string()));
+    }
+
+    #[test]
+    fn star_stays_within_a_component() {
+        let dirs = kept("star", &["a*b"]);
+        assert!(!dirs.contains(&"ab".to_string()));
+        assert!(dirs.contains(&"a/x/b".to_string()));
+
+        let dirs = kept("star-path", &["a/*"]);
+        assert!(dirs.contains(&"a".to_string()));
+        assert!(!dirs.contains(&"a/x".to_string()));
+    }
+
+    #[test]
+    fn name_patterns_match_at_any_depth() {
+        let dirs = kept("name", &["a"]);
+        assert!(!dirs.iter().any(|dir| dir == "a" || dir.ends_with("/a")));
+        assert!(dirs.contains(&"lib/src".to_string()));
+
+        let dirs = kept("name-glob", &["tar*"]);
+        assert!(!dirs.contains(&"deep/er/target".to_string()));
+        assert!(dirs.contains(&"deep/er".to_string()));
+    }
+}
@@ -0,0 +1,533 @@
+use std::ffi::OsStr;
+use std::fs::{FileType, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::Receiver;
+
+use clap::ValueEnum;
+use walkdir::WalkDir;
+
+use crate::entry::Entry;
+use crate::error::Error;
+use crate::follow;
+use crate::ignores::Ignore;
+use crate::matcher::{And, Case, Extension, Matcher, Name, Or};
+use crate::parallel;
+use crate::prune::{Prune, is_hidden};
+use crate::sort::{Order, SortKey, Sorted};
+
+/// Which kinds of entries a search reports. Symlinks are classified as
+/// links, not by what they point to.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum FileTypeFilter {
+    #[value(alias = "f")]
+    File,
+    #[value(alias = "d", alias = "directory")]
+    Dir,
+    #[value(alias = "l", alias = "link")]
+    Symlink,
+    /// A symlink whose target does not exist.
+    BrokenSymlink,
+    Socket,
+    #[value(alias = "pipe")]
+    Fifo,
+    BlockDevice,
+    CharDevice,
+    /// A regular file with any execute bit set.
+    #[value(alias = "x")]
+    Executable,
+    #[default]
+    Any,
+}
+
+/// Describes what to look for and where.
+///
+/// ```no_run
+/// use lookfor::Query;
+///
+/// for m in Query::new("src").ext("rs").search()?.flatten() {
+///     println!("{}", m.path().display());
+/// }
+/// # Ok::<(), lookfor::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Query {
+    root: PathBuf,
+    names: Vec<String>,
+    regex: bool,
+    glob: bool,
+    full_path: bool,
+    case: Case,
+    exts: Vec<String>,
+    min_depth: usize,
+    max_depth: Option<usize>,
+    follow: bool,
+    follow_depth: Option<usize>,
+    one_file_system: bool,
+    exclude_fstypes: Vec<String>,
+    hidden: bool,
+    exclude_dirs: Vec<String>,
+    ignore: bool,
+    ignore_vcs: bool,
+    file_types: Vec<FileTypeFilter>,
+    filters: And,
+    threads: usize,
+    sort: Option<SortKey>,
+    reverse: bool,
+    top: Option<usize>,
+}
+
+impl Query {
+    /// Search everything below `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Query {
+            root: root.into(),
+            names: Vec::new(),
+            regex: false,
+            glob: false,
+            full_path: false,
+            case: Case::Smart,
+            exts: Vec::new(),
+            min_depth: 0,
+            max_depth: None,
+            follow: false,
+            follow_depth: None,
+            one_file_system: false,
+            exclude_fstypes: Vec::new(),
+            hidden: false,
+            exclude_dirs: Vec::new(),
+            ignore: true,
+            ignore_vcs: true,
+            file_types: Vec::new(),
+            filters: And::new(),
+            threads: 1,
+            sort: None,
+            reverse: false,
+            top: None,
+        }
+    }
+
+    /// Match on file/directory name (substring, or regex/glob with
+    /// [`Query::regex`]/[`Query::glob`]). Repeat to match any of several.
+    pub fn name(mut self, pattern: impl Into<String>) -> Self {
+        self.names.push(pattern.into());
+        self
+    }
+
+    /// Treat the name pattern as a regular expression.
+    pub fn regex(mut self, yes: bool) -> Self {
+        self.regex = yes;
+        self
+    }
+
+    /// Treat the name pattern as a shell glob.
+    pub fn glob(mut self, yes: bool) -> Self {
+        self.glob = yes;
+        self
+    }
+
+    /// Match the name pattern against the path relative to the root.
+    pub fn full_path(mut self, yes: bool) -> Self {
+        self.full_path = yes;
+        self
+    }
+
+    /// How name and extension patterns treat letter case (smart by default).
+    pub fn case(mut self, case: Case) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Match on file extension. Repeat to match any of several.
+    pub fn ext(mut self, ext: impl Into<String>) -> Self {
+        self.exts.push(ext.into());
+        self
+    }
+
+    /// Don't report entries above this depth (0 = the root itself, 1 = its
+    /// entries, ...). Shallower directories are still walked.
+    pub fn min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Don't descend below this depth (0 = only the root, 1 = its entries).
+    /// Must not be less than [`Query::min_depth`].
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Only report entries at exactly this depth.
+    pub fn exact_depth(self, depth: usize) -> Self {
+        self.min_depth(depth).max_depth(depth)
+    }
+
+    /// Descend into symlinked directories and match links by what they point
+    /// to. Links leading back to a directory being walked are reported as
+    /// [`Error::Loop`] instead of followed.
+    pub fn follow(mut self, yes: bool) -> Self {
+        self.follow = yes;
+        self
+    }
+
+    /// With [`Query::follow`], descend through at most `links` symlinks on any
+    /// one path. Directories past the budget are still reported, just not
+    /// entered.
+    pub fn follow_depth(mut self, links: usize) -> Self {
+        self.follow_depth = Some(links);
+        self
+    }
+
+    /// Don't descend into directories on other filesystems than the root's,
+    /// like find's `-xdev`. Their mount points are still reported.
+    pub fn one_file_system(mut self, yes: bool) -> Self {
+        self.one_file_system = yes;
+        self
+    }
+
+    /// Skip everything on filesystems of this type (`nfs`, `fuse`, `tmpfs`,
+    /// ...), read from the mount table on Linux, the root included. Repeat
+    /// to skip several.
+    pub fn exclude_fstype(mut self, fs_type: impl Into<String>) -> Self {
+        self.exclude_fstypes.push(fs_type.into());
+        self
+    }
+
+    /// Include hidden files and directories. When off, hidden directories are
+    /// not descended into at all.
+    pub fn hidden(mut self, yes: bool) -> Self {
+        self.hidden = yes;
+        self
+    }
+
+    /// Never descend into directories matching `glob`. Globs containing a `/`
+    /// match the path relative to the root, others the directory name.
+    pub fn exclude_dir(mut self, glob: impl Into<String>) -> Self {
+        self.exclude_dirs.push(glob.into());
+        self
+    }
+
+    /// Honor `.lookforignore` and `.ignore` files, plus the git rules below
+    /// (on by default).
+    pub fn ignore(mut self, yes: bool) -> Self {
+        self.ignore = yes;
+        self
+    }
+
+    /// Honor `.gitignore`, `.git/info/exclude` and `core.excludesFile` inside
+    /// git repositories (on by default, requires [`Query::ignore`]).
+    pub fn ignore_vcs(mut self, yes: bool) -> Self {
+        self.ignore_vcs = yes;
+        self
+    }
+
+    /// Only report entries of the given type. Repeat to match any of several.
+    pub fn file_type(mut self, file_type: FileTypeFilter) -> Self {
+        self.file_types.push(file_type);
+        self
+    }
+
+    /// Additionally require `matcher` to match; filters are ANDed together.
+    pub fn filter(mut self, matcher: impl Matcher + 'static) -> Self {
+        self.filters.push(matcher);
+        self
+    }
+
+    /// Walk with `threads` worker threads (0 = one per CPU). With more than
+    /// one, matches arrive in no particular order unless [`Query::sort`] is
+    /// set.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Collect all matches and report them ordered by `key`. The walk then
+    /// runs to the end on the first call to `next`.
+    pub fn sort(mut self, key: SortKey) -> Self {
+        self.sort = Some(key);
+        self
+    }
+
+    /// Reverse the [`Query::sort`] order.
+    pub fn reverse(mut self, yes: bool) -> Self {
+        self.reverse = yes;
+        self
+    }
+
+    /// Report only the first `n` matches in [`Query::sort`] order, e.g. the
+    /// 10 largest files. Only those `n` are held in memory. Has no effect
+    /// without a sort key.
+    pub fn top(mut self, n: usize) -> Self {
+        self.top = Some(n);
+        self
+    }
+
+    /// Start walking. Fails only if the query itself is invalid.
+    pub fn search(&self) -> Result<Search, Error> {
+        if let Some(max) = self.max_depth
+            && self.min_depth > max
+        {
+            return Err(Error::Value {
+                kind: "minimum depth",
+                value: self.min_depth.to_string(),
+                reason: format!("greater than the maximum depth {max}"),
+            });
+        }
+
+        let mut matcher = And::new();
+
+        if !self.file_types.contains(&FileTypeFilter::Any) {
+            let any = self.file_types.iter().copied().fold(Or::new(), Or::with);
+            if !any.is_empty() {
+                matcher.push(any);
+            }
+        }
+        if !self.names.is_empty() {
+            let name = if self.regex {
+                Name::regexes(&self.names, self.case)?
+            } else if self.glob {
+                Name::globs(&self.names, self.case)?
+            } else {
+                Name::substrings(self.names.clone(), self.case)
+            };
+            matcher.push(name.full_path(self.full_path));
+        }
+        if !self.exts.is_empty() {
+            matcher.push(Extension::any_of(self.exts.clone(), self.case));
+        }
+        if !self.filters.is_empty() {
+            matcher.push(self.filters.clone());
+        }
+
+        let prune = Prune::new(
+            &self.root,
+            self.hidden,
+            &self.exclude_dirs,
+            self.ignore,
+            self.ignore_vcs,
+        )?
+        .one_file_system(self.one_file_system)
+        .exclude_fstypes(&self.exclude_fstypes);
+        let filter = Filter {
+            matcher,
+            hidden: self.hidden,
+            min_depth: self.min_depth,
+        };
+
+        let threads = match self.threads {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+
+        let inner = if threads == 1 {
+            self.walk(prune, filter)
+        } else {
+            Inner::Parallel(parallel::spawn(
+                parallel::Walk {
+                    root: self.root.clone(),
+                    max_depth: self.max_depth,
+                    follow: self.follow,
+                    follow_depth: self.follow_depth,
+                    prune,
+                    filter,
+                },
+                threads,
+            ))
+        };
+
+        let inner = match self.sort {
+            Some(key) => {
+                let order = Order {
+                    key,
+                    reverse: self.reverse,
+                };
+                Inner::Sorted(Box::new(Sorted::new(Search { inner }, order, self.top)))
+            }
+            None => inner,
+        };
+
+        Ok(Search { inner })
+    }
+
+    /// The single-threaded walk, in `walkdir` order.
+    fn walk(&self, prune: Prune, filter: Filter) -> Inner {
+        let mut walker = WalkDir::new(&self.root).follow_links(self.follow);
+
+        if let Some(depth) = self.max_depth {
+            walker = walker.max_depth(depth);
+        }
+
+        Inner::Walk(Box::new(Walker {
+            walker: walker.into_iter(),
+            prune,
+            filter,
+            rules: Vec::new(),
+            links: Vec::new(),
+            follow_depth: self.follow_depth,
+        }))
+    }
+}
+
+/// State of the single-threaded walk. Pruning happens here rather than in
+/// `walkdir`'s `filter_entry` so that entries it reports as errors, like
+/// dangling links when following, go through the same rules.
+struct Walker {
+    walker: walkdir::IntoIter,
+    prune: Prune,
+    filter: Filter,
+    /// Ignore rules of the directories on the path to the current entry,
+    /// indexed by depth; walkdir yields each directory before its contents.
+    rules: Vec<Arc<Ignore>>,
+    /// Symlinks followed to reach each of those directories.
+    links: Vec<usize>,
+    follow_depth: Option<usize>,
+}
+
+impl Walker {
+    fn next(&mut self) -> Option<Result<Match, Error>> {
+        loop {
+            let (entry, depth) = match self.walker.next()? {
+                Ok(entry) => {
+                    let depth = entry.depth();
+                    (entry, depth)
+                }
+                Err(e) => match follow::broken_link(&e) {
+                    Some(link) => (link, e.depth()),
+                    None => return Some(Err(e.into())),
+                },
+            };
+
+            self.rules.truncate(depth);
+            self.links.truncate(depth);
+
+            let is_dir = entry.file_type().is_dir();
+
+            if !self
+                .prune
+                .keep(&entry, depth, self.rules.last().map(|r| &**r))
+            {
+                if is_dir {
+                    self.walker.skip_current_dir();
+                }
+                continue;
+            }
+
+            if is_dir {
+                let links = self.links.last().copied().unwrap_or(0)
+                    + usize::from(depth > 0 && entry.path_is_symlink());
+
+                if self.follow_depth.is_some_and(|max| links > max)
+                    || !self.prune.descend(&entry, depth)
+                {
+                    self.walker.skip_current_dir();
+                } else {
+                    self.rules
+                        .extend(self.prune.rules_for(&entry, self.rules.last()));
+                    self.links.push(links);
+                }
+            }
+
+            if let Some(m) = self.filter.check(Entry::new(entry, depth)) {
+                return Some(Ok(m));
+            }
+        }
+    }
+}
+
+/// The last step for every walked entry, shared by all walkers.
+pub(crate) struct Filter {
+    matcher: And,
+    hidden: bool,
+    /// Applied here rather than through `walkdir`, whose `min_depth` would
+    /// hide the shallower directories from the pruning.
+    min_depth: usize,
+}
+
+impl Filter {
+    pub(crate) fn check(&self, entry: Entry) -> Option<Match> {
+        if entry.depth() < self.min_depth {
+            return None;
+        }
+
+        // The root is walked even when hidden (e.g. `.`), just not reported
+        if entry.depth() == 0 && !self.hidden && is_hidden(entry.dir_entry()) {
+            return None;
+        }
+
+        self.matcher.is_match(&entry).then_some(Match { entry })
+    }
+}
+
+/// Iterator over the entries matching a [`Query`].
+///
+/// Entries that could not be read are yielded as errors; the walk continues
+/// past them.
+pub struct Search {
+    inner: Inner,
+}
+
+enum Inner {
+    Walk(Box<Walker>),
+    Parallel(Receiver<Result<Match, Error>>),
+    Sorted(Box<Sorted<Search>>),
+}
+
+impl Iterator for Search {
+    type Item = Result<Match, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Inner::Walk(walker) => walker.next(),
+            Inner::Parallel(results) => results.recv().ok(),
+            Inner::Sorted(results) => results.next(),
+        }
+    }
+}
+
+/// A single entry that satisfied the query.
+#[derive(Clone, Debug)]
+pub struct Match {
+    entry: Entry,
+}
+
+impl Match {
+    /// Full path, starting with the query root.
+    pub fn path(&self) -> &Path {
+        self.entry.path()
+    }
+
+    pub fn into_path(self) -> PathBuf {
+        self.entry.into_dir_entry().into_path()
+    }
+
+    /// Final path component (the root itself when it has none, e.g. `.`).
+    pub fn file_name(&self) -> &OsStr {
+        self.entry.file_name()
+    }
+
+    /// Depth below the query root (the root is 0).
+    pub fn depth(&self) -> usize {
+        self.entry.depth()
+    }
+
+    /// Type of the entry; symlinks are only resolved with [`Query::follow`].
+    pub fn file_type(&self) -> FileType {
+        self.entry.file_type()
+    }
+
+    /// Metadata of the entry, reusing whatever the filters already fetched.
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.entry.metadata()
+    }
+
+    /// Detected MIME type, see [`Entry::mime`].
+    pub fn mime(&self) -> Option<&'static str> {
+        self.entry.mime()
+    }
+
+    /// The entry as the matchers saw it.
+    pub fn entry(&self) -> &Entry {
+        &self.entry
+    }
+}
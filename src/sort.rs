@@ -0,0 +1,303 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::SystemTime;
+
+use clap::ValueEnum;
+
+use crate::error::Error;
+use crate::query::Match;
+
+/// Order in which a search reports its matches. Ties are broken by path, so
+/// the order doesn't depend on the walk.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// By file name
+    Name,
+    /// Lexicographically by full path
+    Path,
+    /// Largest first
+    Size,
+    /// Most recently modified first
+    Mtime,
+    /// Shallowest first
+    Depth,
+    /// By extension, ignoring case; names without one come first
+    Ext,
+}
+
+/// A sort key and its direction.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Order {
+    pub(crate) key: SortKey,
+    pub(crate) reverse: bool,
+}
+
+impl Order {
+    fn compare(self, a: &Match, b: &Match) -> Ordering {
+        let by_key = match self.key {
+            SortKey::Name => a
+                .file_name()
+                .as_encoded_bytes()
+                .cmp(b.file_name().as_encoded_bytes()),
+            SortKey::Path => Ordering::Equal,
+            SortKey::Size => size(b).cmp(&size(a)),
+            SortKey::Mtime => mtime(b).cmp(&mtime(a)),
+            SortKey::Depth => a.depth().cmp(&b.depth()),
+            SortKey::Ext => ext(a).cmp(&ext(b)),
+        };
+        let order = by_key.then_with(|| a.path().cmp(b.path()));
+
+        if self.reverse { order.reverse() } else { order }
+    }
+}
+
+fn size(m: &Match) -> u64 {
+    m.metadata().map_or(0, |md| md.len())
+}
+
+/// Entries without a modification time sort as the oldest.
+fn mtime(m: &Match) -> SystemTime {
+    m.metadata()
+        .and_then(|md| md.modified().ok())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+fn ext(m: &Match) -> Vec<u8> {
+    m.path()
+        .extension()
+        .map(|ext| ext.as_encoded_bytes().to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+/// A match that orders by where it goes in the output.
+struct Ranked {
+    m: Match,
+    order: Order,
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.order.compare(&self.m, &other.m)
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+/// Sorts the results of `unsorted` when first asked for one, so a search
+/// hands out its iterator before walking anything.
+pub(crate) struct Sorted<I> {
+    unsorted: Option<I>,
+    order: Order,
+    top: Option<usize>,
+    results: std::vec::IntoIter<Result<Match, Error>>,
+}
+
+impl<I: Iterator<Item = Result<Match, Error>>> Sorted<I> {
+    pub(crate) fn new(unsorted: I, order: Order, top: Option<usize>) -> Self {
+        Sorted {
+            unsorted: Some(unsorted),
+            order,
+            top,
+            results: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Match, Error>>> Iterator for Sorted<I> {
+    type Item = Result<Match, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(unsorted) = self.unsorted.take() {
+            self.results = sorted(unsorted, self.order, self.top).into_iter();
+        }
+        self.results.next()
+    }
+}
+
+/// Drains `results` and returns them in `order`, keeping only the first
+/// `top` matches if given. Errors come first, in the order they happened.
+fn sorted(
+    results: impl Iterator<Item = Result<Match, Error>>,
+    order: Order,
+    top: Option<usize>,
+) -> Vec<Result<Match, Error>> {
+    let mut errors = Vec::new();
+
+    let matches = match top {
+        Some(n) => {
+            // The heap holds the best `n` so far with the worst on top, so
+            // memory stays bounded however many entries match
+            let mut heap = BinaryHeap::with_capacity(n.saturating_add(1).min(4096));
+
+            for result in results {
+                match result {
+                    Ok(m) => {
+                        heap.push(Ranked { m, order });
+                        if heap.len() > n {
+                            heap.pop();
+                        }
+                    }
+                    Err(e) => errors.push(Err(e)),
+                }
+            }
+
+            heap.into_sorted_vec().into_iter().map(|r| r.m).collect()
+        }
+        None => {
+            let mut matches = Vec::new();
+
+            for result in results {
+                match result {
+                    Ok(m) => matches.push(m),
+                    Err(e) => errors.push(Err(e)),
+                }
+            }
+
+            matches.sort_by(|a, b| order.compare(a, b));
+            matches
+        }
+    };
+
+    errors.extend(matches.into_iter().map(Ok));
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::query::Query;
+
+    /// Matches for files `a` to `d`, sized 3, 1, 2 and 2 bytes, in walk order.
+    fn matches(test: &str) -> Vec<Match> {
+        let root = std::env::temp_dir().join(format!("lookfor-sort-{}-{test}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        for (name, contents) in [("a", "aaa"), ("b", "b"), ("c", "cc"), ("d", "dd")] {
+            fs::write(root.join(name), contents).unwrap();
+        }
+
+        let matches: Vec<Match> = Query::new(&root)
+            .min_depth(1)
+            .search()
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        // Metadata is cached, so the files can go
+        for m in &matches {
+            m.metadata();
+        }
+        fs::remove_dir_all(&root).unwrap();
+
+        matches
+    }
+
+    fn names(results: Vec<Result<Match, Error>>) -> Vec<String> {
+        results
+            .into_iter()
+            .map(|r| match r {
+                Ok(m) => m.file_name().to_string_lossy().into_owned(),
+                Err(e) => format!("error: {e}"),
+            })
+            .collect()
+    }
+
+    fn by(key: SortKey, reverse: bool) -> Order {
+        Order { key, reverse }
+    }
+
+    #[test]
+    fn sorts_all_without_top() {
+        let sizes = sorted(
+            matches("all").into_iter().map(Ok),
+            by(SortKey::Size, false),
+            None,
+        );
+
+        // Largest first, the equal sizes by path
+        assert_eq!(names(sizes), ["a", "c", "d", "b"]);
+    }
+
+    #[test]
+    fn top_keeps_the_best() {
+        let all = matches("top");
+
+        let top = sorted(
+            all.clone().into_iter().map(Ok),
+            by(SortKey::Size, false),
+            Some(2),
+        );
+        assert_eq!(names(top), ["a", "c"]);
+
+        let top = sorted(
+            all.clone().into_iter().map(Ok),
+            by(SortKey::Name, false),
+            Some(10),
+        );
+        assert_eq!(names(top), ["a", "b", "c", "d"]);
+
+        let none = sorted(all.into_iter().map(Ok), by(SortKey::Size, false), Some(0));
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn reverse_flips_the_tie_break() {
+        let all = matches("reverse");
+
+        let sizes = sorted(
+            all.clone().into_iter().map(Ok),
+            by(SortKey::Size, true),
+            None,
+        );
+        assert_eq!(names(sizes), ["b", "d", "c", "a"]);
+
+        let top = sorted(all.into_iter().map(Ok), by(SortKey::Size, true), Some(2));
+        assert_eq!(names(top), ["b", "d"]);
+    }
+
+    #[test]
+    fn errors_come_first() {
+        let all = matches("errors");
+        let results = || {
+            let mut results: Vec<Result<Match, Error>> = all.iter().cloned().map(Ok).collect();
+            results.insert(1, Err(Error::Expr("one".to_string())));
+            results.push(Err(Error::Expr("two".to_string())));
+            results.into_iter()
+        };
+
+        let sorted_all = sorted(results(), by(SortKey::Path, false), None);
+        assert_eq!(
+            names(sorted_all),
+            [
+                "error: Invalid expression: one",
+                "error: Invalid expression: two",
+                "a",
+                "b",
+                "c",
+                "d"
+            ]
+        );
+
+        let top = sorted(results(), by(SortKey::Path, false), Some(1));
+        assert_eq!(
+            names(top),
+            [
+                "error: Invalid expression: one",
+                "error: Invalid expression: two",
+                "a"
+            ]
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! `--format` templates: text with `{placeholder}`s filled in per match.
+
+use std::path::Path;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use lookfor::matcher::TimeField;
+use lookfor::{Error, Match};
+use serde_json::Value;
+
+use crate::output::Field;
+
+/// A parsed template such as `{size:h}\t{mtime:%Y-%m-%d}\t{path}`.
+///
+/// Placeholders are the `--fields` names plus `stem` (name without
+/// extension), `parent` (containing directory) and `owner` (same as
+/// `user`). `{size:h}` gives a
+/// human-readable size and time fields take a strftime format, e.g.
+/// `{mtime:%Y-%m-%d %H:%M}`; without one they render as local RFC 3339.
+/// `{{` and `}}` are literal braces, and `\t`, `\n`, `\0` and `\\` are
+/// unescaped so templates can be given in single quotes.
+#[derive(Clone, Debug)]
+pub struct Template(Vec<Piece>);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    /// Path-like values, written as raw bytes.
+    Path(PathPart),
+    Time(TimeField, Option<String>),
+    HumanSize,
+    Field(Field),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PathPart {
+    Path,
+    RelativePath,
+    Name,
+    Stem,
+    Ext,
+    Parent,
+    Target,
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Self, Error> {
+        let invalid = |reason: String| Error::Value {
+            kind: "format",
+            value: template.to_string(),
+            reason,
+        };
+
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => text.push('\t'),
+                    Some('n') => text.push('\n'),
+                    Some('0') => text.push('\0'),
+                    Some('\\') => text.push('\\'),
+                    Some(other) => {
+                        text.push('\\');
+                        text.push(other);
+                    }
+                    None => text.push('\\'),
+                },
+                '}' => {
+                    if chars.next() != Some('}') {
+                        return Err(invalid("unmatched '}' (use '}}' for a literal one)".into()));
+                    }
+                    text.push('}');
+                }
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| invalid("unclosed '{'".into()))?;
+                    let placeholder = &rest[..end];
+                    chars = rest[end + 1..].chars();
+
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(Piece::parse(placeholder).map_err(invalid)?);
+                }
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+
+        Ok(Template(pieces))
+    }
+
+    /// Appends the template filled in for `m` to `out`.
+    pub fn render(&self, m: &Match, out: &mut Vec<u8>) {
+        for piece in &self.0 {
+            match piece {
+                Piece::Text(text) => out.extend_from_slice(text.as_bytes()),
+                Piece::Path(part) => {
+                    if let Some(path) = part.of(m) {
+                        out.extend_from_slice(path.as_os_str().as_encoded_bytes());
+                    }
+                }
+                Piece::Time(field, format) => {
+                    let time = m.metadata().and_then(|md| field.of(md));
+
+                    if let Some(time) = time {
+                        let local = DateTime::<Local>::from(time);
+                        let text = match format {
+                            Some(format) => local.format(format).to_string(),
+                            None => local.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+                        };
+                        out.extend_from_slice(text.as_bytes());
+                    }
+                }
+                Piece::HumanSize => {
+                    if let Some(md) = m.metadata() {
+                        out.extend_from_slice(human_size(md.len()).as_bytes());
+                    }
+                }
+                Piece::Field(field) => match field.value(m) {
+                    Value::Null => {}
+                    Value::String(s) => out.extend_from_slice(s.as_bytes()),
+                    other => out.extend_from_slice(other.to_string().as_bytes()),
+                },
+            }
+        }
+    }
+}
+
+impl Piece {
+    fn parse(placeholder: &str) -> Result<Piece, String> {
+        let (name, spec) = match placeholder.split_once(':') {
+            Some((name, spec)) => (name, Some(spec)),
+            None => (placeholder, None),
+        };
+
+        let part = match name {
+            "path" => Some(PathPart::Path),
+            "relative_path" => Some(PathPart::RelativePath),
+            "name" => Some(PathPart::Name),
+            "stem" => Some(PathPart::Stem),
+            "ext" => Some(PathPart::Ext),
+            "parent" => Some(PathPart::Parent),
+            "target" => Some(PathPart::Target),
+            _ => None,
+        };
+        let time = match name {
+            "mtime" => Some(TimeField::Modified),
+            "atime" => Some(TimeField::Accessed),
+            "ctime" => Some(TimeField::Changed),
+            "created" => Some(TimeField::Created),
+            _ => None,
+        };
+
+        match (part, time, spec) {
+            (Some(part), _, None) => Ok(Piece::Path(part)),
+            (_, Some(field), None) => Ok(Piece::Time(field, None)),
+            (_, Some(field), Some(format)) => {
+                if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                    return Err(format!("invalid time format '{format}'"));
+                }
+                Ok(Piece::Time(field, Some(format.to_string())))
+            }
+            _ if name == "size" && spec == Some("h") => Ok(Piece::HumanSize),
+            (None, None, None) if name == "owner" => Ok(Piece::Field(Field::User)),
+            (None, None, None) => Field::from_str(name, false)
+                .map(Piece::Field)
+                .map_err(|_| format!("unknown placeholder '{{{name}}}'")),
+            _ => Err(format!("'{{{name}}}' takes no ':' format")),
+        }
+    }
+}
+
+impl PathPart {
+    fn of(self, m: &Match) -> Option<std::borrow::Cow<'_, Path>> {
+        let path = m.path();
+
+        Some(match self {
+            PathPart::Path => path.into(),
+            PathPart::RelativePath => m.entry().relative_path().into(),
+            PathPart::Name => Path::new(m.file_name()).into(),
+            PathPart::Stem => Path::new(path.file_stem()?).into(),
+            PathPart::Ext => Path::new(path.extension()?).into(),
+            PathPart::Parent => path.parent()?.into(),
+            PathPart::Target => std::fs::read_link(path).ok()?.into(),
+        })
+    }
+}
+
+/// Sizes like `ls -h`: powers of 1024 with one decimal below 10.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+
+    if bytes < 1024 {
+        return bytes.to_string();
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if size < 10.0 {
+        format!("{:.1}{}", size, UNITS[unit])
+    } else {
+        format!("{:.0}{}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(template: &str) -> Result<Vec<Piece>, String> {
+        Template::parse(template)
+            .map(|t| t.0)
+            .map_err(|e| e.to_string())
+    }
+
+    fn text(s: &str) -> Piece {
+        Piece::Text(s.to_string())
+    }
+
+    #[test]
+    fn placeholders() {
+        assert_eq!(
+            parse("{name} is {size:h}"),
+            Ok(vec![
+                Piece::Path(PathPart::Name),
+                text(" is "),
+                Piece::HumanSize
+            ])
+        );
+        assert_eq!(
+            parse("{mtime:%Y}{ctime}"),
+            Ok(vec![
+                Piece::Time(TimeField::Modified, Some("%Y".to_string())),
+                Piece::Time(TimeField::Changed, None),
+            ])
+        );
+        assert_eq!(
+            parse("{owner}:{group}"),
+            Ok(vec![
+                Piece::Field(Field::User),
+                text(":"),
+                Piece::Field(Field::Group)
+            ])
+        );
+    }
+
+    #[test]
+    fn escapes_and_braces() {
+        assert_eq!(
+            parse(r"a\tb\n\0\\{{c}}\q"),
+            Ok(vec![text("a\tb\n\0\\{c}\\q")])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |template: &str, reason: &str| {
+            assert_eq!(
+                parse(template),
+                Err(format!("Invalid format '{template}': {reason}"))
+            );
+        };
+
+        error("{bogus}", "unknown placeholder '{bogus}'");
+        error("{name", "unclosed '{'");
+        error("name}", "unmatched '}' (use '}}' for a literal one)");
+        error("{name:x}", "'{name}' takes no ':' format");
+        error("{size:k}", "'{size}' takes no ':' format");
+        error("{mtime:%Q}", "invalid time format '%Q'");
+    }
+
+    #[test]
+    fn human_sizes() {
+        assert_eq!(human_size(1023), "1023");
+        assert_eq!(human_size(1536), "1.5K");
+        assert_eq!(human_size(20 << 20), "20M");
+        assert_eq!(human_size(u64::MAX), "16E");
+    }
+}
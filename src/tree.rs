@@ -0,0 +1,165 @@
+//! `--tree` output: matches drawn under their ancestor directories, like
+//! `tree -P`.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{MAIN_SEPARATOR_STR, Path};
+
+use lookfor::Match;
+
+use crate::color::Colors;
+
+/// Matches collected until the end, since any later one may land anywhere
+/// in the tree.
+pub struct Tree {
+    /// `nodes[0]` holds the search roots; it isn't printed itself.
+    nodes: Vec<Node>,
+}
+
+struct Node {
+    /// The raw name; the whole path for roots.
+    name: Vec<u8>,
+    /// The name as printed, colored if enabled.
+    label: Vec<u8>,
+    matched: bool,
+    /// By raw name, so siblings come out sorted.
+    children: BTreeMap<Vec<u8>, usize>,
+    /// Matches anywhere below this node.
+    matches: usize,
+}
+
+impl Default for Tree {
+    fn default() -> Self {
+        Tree {
+            nodes: vec![Node::new(Vec::new(), Vec::new())],
+        }
+    }
+}
+
+impl Node {
+    fn new(name: Vec<u8>, label: Vec<u8>) -> Self {
+        Node {
+            name,
+            label,
+            matched: false,
+            children: BTreeMap::new(),
+            matches: 0,
+        }
+    }
+}
+
+impl Tree {
+    /// Adds `m` along with the directories between it and its search root.
+    pub fn push(&mut self, m: &Match, colors: Option<&Colors>) {
+        let root = m.path().ancestors().nth(m.depth()).unwrap_or(m.path());
+        let names = m.entry().relative_path().components();
+
+        let mut node = self.child(0, root, colors);
+        for name in names {
+            self.nodes[node].matches += 1;
+            node = self.child(node, Path::new(name.as_os_str()), colors);
+        }
+
+        let node = &mut self.nodes[node];
+        node.matched = true;
+        node.label.clear();
+        // The root keeps its path as given, everything else just the name
+        match (colors, m.depth()) {
+            (Some(colors), 0) => colors.paint(m, &mut node.label),
+            (Some(colors), _) => colors.paint_name(m, &mut node.label),
+            (None, _) => node.label.extend_from_slice(&node.name),
+        }
+    }
+
+    /// The child of `parent` called `name`, added as a plain directory if
+    /// it isn't there yet.
+    fn child(&mut self, parent: usize, name: &Path, colors: Option<&Colors>) -> usize {
+        let name = name.as_os_str().as_encoded_bytes();
+
+        if let Some(&child) = self.nodes[parent].children.get(name) {
+            return child;
+        }
+
+        let mut label = Vec::new();
+        match colors {
+            Some(colors) => colors.paint_dir(name, &mut label),
+            None => label.extend_from_slice(name),
+        }
+
+        let child = self.nodes.len();
+        self.nodes.push(Node::new(name.to_vec(), label));
+        self.nodes[parent].children.insert(name.to_vec(), child);
+        child
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        for &root in self.nodes[0].children.values() {
+            let root = self.write_line(out, root)?;
+            self.write_children(out, root, &mut Vec::new())?;
+        }
+
+        Ok(())
+    }
+
+    fn write_children(
+        &self,
+        out: &mut impl Write,
+        node: usize,
+        prefix: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        let children = &self.nodes[node].children;
+
+        for (i, &child) in children.values().enumerate() {
+            let last = i + 1 == children.len();
+
+            out.write_all(prefix)?;
+            out.write_all(if last { "└── " } else { "├── " }.as_bytes())?;
+            let child = self.write_line(out, child)?;
+
+            let len = prefix.len();
+            prefix.extend_from_slice(if last { "    " } else { "│   " }.as_bytes());
+            self.write_children(out, child, prefix)?;
+            prefix.truncate(len);
+        }
+
+        Ok(())
+    }
+
+    /// Writes the label of `node`, joined with the directories below it as
+    /// long as they form a chain, and the number of matches below. Returns
+    /// the last node of the chain.
+    fn write_line(&self, out: &mut impl Write, mut node: usize) -> io::Result<usize> {
+        out.write_all(&self.nodes[node].label)?;
+
+        while let Some(next) = self.chained(node) {
+            // Roots like `/` already end with a separator
+            if !self.nodes[node]
+                .name
+                .ends_with(MAIN_SEPARATOR_STR.as_bytes())
+            {
+                out.write_all(MAIN_SEPARATOR_STR.as_bytes())?;
+            }
+            node = next;
+            out.write_all(&self.nodes[node].label)?;
+        }
+
+        match self.nodes[node].matches {
+            0 => writeln!(out),
+            n => writeln!(out, " ({n})"),
+        }?;
+
+        Ok(node)
+    }
+
+    /// The only child of `node` if it goes on the same line: `node` isn't a
+    /// match itself and the child is a directory with entries of its own.
+    /// Leaves always get their own line.
+    fn chained(&self, node: usize) -> Option<usize> {
+        let node = &self.nodes[node];
+        let &child = node.children.values().next()?;
+
+        let chained =
+            !node.matched && node.children.len() == 1 && !self.nodes[child].children.is_empty();
+        chained.then_some(child)
+    }
+}
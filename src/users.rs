@@ -0,0 +1,107 @@
+//! User and group database lookups, cached since walks ask about the same
+//! few ids over and over.
+
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_char};
+use std::sync::{LazyLock, Mutex};
+
+static USER_NAMES: LazyLock<Mutex<HashMap<u32, Option<String>>>> = LazyLock::new(Default::default);
+static GROUP_NAMES: LazyLock<Mutex<HashMap<u32, Option<String>>>> = LazyLock::new(Default::default);
+
+/// Name of the user with id `uid`, if it exists.
+pub fn user_name(uid: u32) -> Option<String> {
+    cached(&USER_NAMES, uid, || {
+        lookup(|pwd: &mut libc::passwd, buf, len, result| unsafe {
+            libc::getpwuid_r(uid, pwd, buf, len, result)
+        })
+        .map(|pwd| {
+            unsafe { CStr::from_ptr(pwd.pw_name) }
+                .to_string_lossy()
+                .into_owned()
+        })
+    })
+}
+
+/// Name of the group with id `gid`, if it exists.
+pub fn group_name(gid: u32) -> Option<String> {
+    cached(&GROUP_NAMES, gid, || {
+        lookup(|grp: &mut libc::group, buf, len, result| unsafe {
+            libc::getgrgid_r(gid, grp, buf, len, result)
+        })
+        .map(|grp| {
+            unsafe { CStr::from_ptr(grp.gr_name) }
+                .to_string_lossy()
+                .into_owned()
+        })
+    })
+}
+
+/// Id of the user called `name`.
+pub fn user_id(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+
+    lookup(|pwd: &mut libc::passwd, buf, len, result| unsafe {
+        libc::getpwnam_r(name.as_ptr(), pwd, buf, len, result)
+    })
+    .map(|pwd| pwd.pw_uid)
+}
+
+/// Id of the group called `name`.
+pub fn group_id(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+
+    lookup(|grp: &mut libc::group, buf, len, result| unsafe {
+        libc::getgrnam_r(name.as_ptr(), grp, buf, len, result)
+    })
+    .map(|grp| grp.gr_gid)
+}
+
+fn cached(
+    cache: &Mutex<HashMap<u32, Option<String>>>,
+    id: u32,
+    fetch: impl FnOnce() -> Option<String>,
+) -> Option<String> {
+    if let Some(name) = cache.lock().unwrap().get(&id) {
+        return name.clone();
+    }
+
+    let name = fetch();
+    cache.lock().unwrap().insert(id, name.clone());
+    name
+}
+
+/// Runs one of the reentrant `get{pw,gr}*_r` functions, growing the string
+/// buffer until the record fits. The returned record's strings point into
+/// the buffer, so only copy fields out within the caller's `map`.
+fn lookup<T>(
+    call: impl Fn(&mut T, *mut c_char, usize, *mut *mut T) -> libc::c_int,
+) -> Option<Record<T>> {
+    let mut buf: Vec<c_char> = vec![0; 1024];
+
+    loop {
+        // SAFETY: passwd and group are plain C structs; all-zero is a valid
+        // (if meaningless) value and it is only read after a successful call
+        let mut record: T = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+
+        match call(&mut record, buf.as_mut_ptr(), buf.len(), &mut result) {
+            libc::ERANGE if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+            0 if !result.is_null() => return Some(Record { record, _buf: buf }),
+            _ => return None,
+        }
+    }
+}
+
+/// A looked-up record together with the buffer its strings live in.
+struct Record<T> {
+    record: T,
+    _buf: Vec<c_char>,
+}
+
+impl<T> std::ops::Deref for Record<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.record
+    }
+}